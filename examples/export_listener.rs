@@ -0,0 +1,26 @@
+//! Reference entry point listener.
+//!
+//! Stands in for `rust-gpu-builder` when using `socket_writer`,
+//! printing the accumulated entry points for each export as messages arrive.
+//!
+//! Usage: `cargo run --example export_listener -- [ADDRESS]`
+
+use bevy::utils::HashMap;
+use bevy_rust_gpu::prelude::SocketListener;
+
+fn main() -> std::io::Result<()> {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:9001".to_string());
+
+    let listener = SocketListener::bind(&address)?;
+    println!("Listening on {:}", listener.local_addr()?);
+
+    listener.run(&mut HashMap::default(), |exports| {
+        for (path, entry_points) in exports.iter() {
+            println!("{:}:", path.display());
+            println!("{:}", serde_json::to_string_pretty(entry_points).unwrap());
+        }
+        true
+    })
+}
@@ -0,0 +1,185 @@
+//! Artifact variants tagged with required device features,
+//! resolved against the active [`RenderDevice`] during specialization.
+
+use std::sync::RwLock;
+
+use bevy::{
+    prelude::{default, Handle, Res},
+    render::{renderer::RenderDevice, settings::WgpuFeatures},
+};
+use once_cell::sync::Lazy;
+use rust_gpu_builder_shared::RustGpuBuilderOutput;
+
+/// Static container for the active render device's features, to allow access from `Material::specialize`
+pub static RENDER_DEVICE_FEATURES: Lazy<RwLock<WgpuFeatures>> = Lazy::new(default);
+
+/// A [`RustGpuBuilderOutput`] that may only be used if the render device supports a set of features.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RustGpuVariant {
+    /// Artifact to use if this variant is selected.
+    pub shader: Handle<RustGpuBuilderOutput>,
+    /// Features the render device must support for this variant to be selected.
+    pub features: WgpuFeatures,
+}
+
+impl RustGpuVariant {
+    pub fn new(shader: Handle<RustGpuBuilderOutput>, features: WgpuFeatures) -> Self {
+        RustGpuVariant { shader, features }
+    }
+
+    /// Constructs a variant from a set of SPIR-V capability names.
+    ///
+    /// Returns `None` if any of the capabilities have no corresponding [`WgpuFeatures`] flag,
+    /// as such a variant could never be selected.
+    pub fn from_capabilities(
+        shader: Handle<RustGpuBuilderOutput>,
+        capabilities: &[&str],
+    ) -> Option<Self> {
+        let features = capabilities
+            .iter()
+            .map(|capability| capability_features(capability))
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .fold(WgpuFeatures::empty(), std::ops::BitOr::bitor);
+
+        Some(RustGpuVariant { shader, features })
+    }
+}
+
+/// Returns the [`WgpuFeatures`] required to use a given SPIR-V capability,
+/// or `None` if the capability cannot be enabled via `wgpu`.
+///
+/// Capabilities that are always available (such as `Shader`) map to [`WgpuFeatures::empty`].
+pub fn capability_features(capability: &str) -> Option<WgpuFeatures> {
+    match capability {
+        "Shader" | "Matrix" | "Sampled1D" | "Image1D" | "ImageQuery" | "DerivativeControl"
+        | "SampledCubeArray" | "VulkanMemoryModel" => Some(WgpuFeatures::empty()),
+        "Float16" => Some(WgpuFeatures::SHADER_FLOAT16),
+        "Float64" => Some(WgpuFeatures::SHADER_FLOAT64),
+        "MultiView" => Some(WgpuFeatures::MULTIVIEW),
+        "SampledImageArrayNonUniformIndexing" | "StorageBufferArrayNonUniformIndexing" => {
+            Some(WgpuFeatures::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING)
+        }
+        "UniformBufferArrayNonUniformIndexing" | "StorageImageArrayNonUniformIndexing" => {
+            Some(WgpuFeatures::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING)
+        }
+        // Int64, Geometry, GroupNonUniform* and other capabilities have no `wgpu` equivalent
+        _ => None,
+    }
+}
+
+/// Selects the most specialized variant supported by the provided feature set.
+///
+/// A variant is supported if `features` contains all of its required features.
+/// Of the supported variants, the one requiring the most features is selected,
+/// with ties resolved in favour of the earliest.
+pub fn select_variant(
+    variants: &[RustGpuVariant],
+    features: WgpuFeatures,
+) -> Option<&RustGpuVariant> {
+    variants
+        .iter()
+        .filter(|variant| features.contains(variant.features))
+        .rev()
+        .max_by_key(|variant| variant.features.bits().count_ones())
+}
+
+/// System used to mirror the active render device's features into static storage.
+pub fn render_device_features_system(render_device: Option<Res<RenderDevice>>) {
+    let Some(render_device) = render_device else {
+        return;
+    };
+
+    *RENDER_DEVICE_FEATURES.write().unwrap() = render_device.features();
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::HandleId, prelude::Handle, reflect::TypeUuid, render::settings::WgpuFeatures,
+    };
+    use rust_gpu_builder_shared::RustGpuBuilderOutput;
+
+    use super::{capability_features, select_variant, RustGpuVariant};
+
+    fn variant(id: u64, features: WgpuFeatures) -> RustGpuVariant {
+        RustGpuVariant::new(
+            Handle::weak(HandleId::new(RustGpuBuilderOutput::TYPE_UUID, id)),
+            features,
+        )
+    }
+
+    #[test]
+    fn selects_most_specialized_supported_variant() {
+        let variants = [
+            variant(0, WgpuFeatures::empty()),
+            variant(1, WgpuFeatures::SHADER_FLOAT64),
+            variant(2, WgpuFeatures::SHADER_FLOAT64 | WgpuFeatures::MULTIVIEW),
+            variant(
+                3,
+                WgpuFeatures::SHADER_FLOAT64
+                    | WgpuFeatures::MULTIVIEW
+                    | WgpuFeatures::SHADER_FLOAT16,
+            ),
+        ];
+
+        let selected = select_variant(
+            &variants,
+            WgpuFeatures::SHADER_FLOAT64 | WgpuFeatures::MULTIVIEW | WgpuFeatures::PUSH_CONSTANTS,
+        );
+
+        assert_eq!(selected, Some(&variants[2]));
+    }
+
+    #[test]
+    fn selects_nothing_when_unsupported() {
+        let variants = [
+            variant(0, WgpuFeatures::SHADER_FLOAT64),
+            variant(1, WgpuFeatures::MULTIVIEW),
+        ];
+
+        assert_eq!(select_variant(&variants, WgpuFeatures::empty()), None);
+        assert_eq!(select_variant(&[], WgpuFeatures::all()), None);
+    }
+
+    #[test]
+    fn resolves_ties_in_favour_of_earliest() {
+        let variants = [
+            variant(0, WgpuFeatures::empty()),
+            variant(1, WgpuFeatures::SHADER_FLOAT64),
+            variant(2, WgpuFeatures::MULTIVIEW),
+            variant(3, WgpuFeatures::SHADER_FLOAT16),
+        ];
+
+        let selected = select_variant(
+            &variants,
+            WgpuFeatures::SHADER_FLOAT64 | WgpuFeatures::MULTIVIEW,
+        );
+
+        assert_eq!(selected, Some(&variants[1]));
+    }
+
+    #[test]
+    fn maps_capabilities_to_features() {
+        assert_eq!(capability_features("Shader"), Some(WgpuFeatures::empty()));
+        assert_eq!(
+            capability_features("Float64"),
+            Some(WgpuFeatures::SHADER_FLOAT64)
+        );
+        assert_eq!(capability_features("Geometry"), None);
+        assert_eq!(capability_features("Int64"), None);
+
+        assert_eq!(
+            RustGpuVariant::from_capabilities(
+                Handle::default(),
+                &["Shader", "Float16", "MultiView"]
+            )
+            .map(|variant| variant.features),
+            Some(WgpuFeatures::SHADER_FLOAT16 | WgpuFeatures::MULTIVIEW)
+        );
+        assert_eq!(
+            RustGpuVariant::from_capabilities(Handle::default(), &["Shader", "Geometry"]),
+            None
+        );
+    }
+}
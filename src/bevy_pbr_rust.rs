@@ -2,23 +2,58 @@
 
 use bevy::{prelude::StandardMaterial, render::render_resource::ShaderDefVal};
 
-use crate::{
-    prelude::{EntryPoint, EntryPointName, EntryPointParameters, RustGpuMaterial},
-    EntryPointConstants,
+use crate::prelude::{
+    mapped_shader_defs, metadata_permutation, EntryPoint, RustGpuEntryPointMetadata,
+    RustGpuMaterial,
 };
 
+/// Entry point metadata for the `bevy-pbr-rust` shader crate.
+///
+/// Registered by default, and superseded by any sidecar loaded alongside a newer build of the crate.
+pub fn bevy_pbr_rust_metadata() -> RustGpuEntryPointMetadata {
+    serde_json::from_str(include_str!("bevy-pbr-rust.rust-gpu-meta.json"))
+        .expect("Bundled bevy-pbr-rust metadata is invalid")
+}
+
+/// Shader defs emitted by `MeshPipeline` that no `bevy-pbr-rust` entry point reads.
+const MESH_PIPELINE_IGNORED_SHADER_DEFS: &[&str] = &[
+    "VERTEX_POSITIONS",
+    "VERTEX_NORMALS",
+    "MULTISAMPLED",
+    "TONEMAP_METHOD_NONE",
+    "TONEMAP_METHOD_REINHARD",
+    "TONEMAP_METHOD_REINHARD_LUMINANCE",
+    "TONEMAP_METHOD_ACES_FITTED",
+    "TONEMAP_METHOD_AGX",
+    "TONEMAP_METHOD_SOMEWHAT_BORING_DISPLAY_TRANSFORM",
+    "TONEMAP_METHOD_BLENDER_FILMIC",
+    "TONEMAP_METHOD_TONY_MC_MAPFACE",
+];
+
+/// Shader defs emitted by `MeshPipeline` that only fragment entry points read.
+const MESH_PIPELINE_FRAGMENT_SHADER_DEFS: &[&str] = &[
+    "VERTEX_UVS",
+    "MAX_DIRECTIONAL_LIGHTS",
+    "MAX_CASCADES_PER_LIGHT",
+    "PREMULTIPLY_ALPHA",
+    "BLEND_PREMULTIPLIED_ALPHA",
+    "BLEND_MULTIPLY",
+    "TONEMAP_IN_SHADER",
+    "DEBAND_DITHER",
+    "ENVIRONMENT_MAP",
+];
+
 /// `bevy_rust_gpu::mesh::entry_points::vertex`
 pub enum MeshVertex {}
 
 impl EntryPoint for MeshVertex {
-    const NAME: EntryPointName = "mesh::entry_points::vertex";
-
-    fn parameters() -> EntryPointParameters {
-        &[
-            (&[("VERTEX_TANGENTS", "some")], "none"),
-            (&[("VERTEX_COLORS", "some")], "none"),
-            (&[("SKINNED", "some")], "none"),
-        ]
+    crate::metadata_entry_point!("mesh::entry_points::vertex");
+
+    fn consumed_shader_defs() -> Vec<&'static str> {
+        let mut shader_defs = mapped_shader_defs(Self::parameters(), Self::constants());
+        shader_defs.extend(MESH_PIPELINE_IGNORED_SHADER_DEFS);
+        shader_defs.extend(MESH_PIPELINE_FRAGMENT_SHADER_DEFS);
+        shader_defs
     }
 }
 
@@ -26,73 +61,21 @@ impl EntryPoint for MeshVertex {
 pub enum MeshFragment {}
 
 impl EntryPoint for MeshFragment {
-    const NAME: EntryPointName = "mesh::entry_points::fragment";
+    crate::metadata_entry_point!("mesh::entry_points::fragment");
 }
 
 /// `bevy_rust_gpu::pbr::entry_points::fragment`
 pub enum PbrFragment {}
 
 impl EntryPoint for PbrFragment {
-    const NAME: EntryPointName = "pbr::entry_points::fragment";
-    fn parameters() -> EntryPointParameters {
-        &[
-            (&[("NO_TEXTURE_ARRAYS_SUPPORT", "texture")], "array"),
-            (&[("VERTEX_UVS", "some")], "none"),
-            (&[("VERTEX_TANGENTS", "some")], "none"),
-            (&[("VERTEX_COLORS", "some")], "none"),
-            (&[("STANDARDMATERIAL_NORMAL_MAP", "some")], "none"),
-            (&[("SKINNED", "some")], "none"),
-            (&[("TONEMAP_IN_SHADER", "some")], "none"),
-            (&[("DEBAND_DITHER", "some")], "none"),
-            (
-                &[
-                    ("BLEND_MULTIPLY", "multiply"),
-                    ("BLEND_PREMULTIPLIED_ALPHA", "blend_premultiplied_alpha"),
-                ],
-                "none",
-            ),
-            (&[("ENVIRONMENT_MAP", "some")], "none"),
-            (&[("PREMULTIPLY_ALPHA", "some")], "none"),
-            (
-                &[
-                    ("CLUSTERED_FORWARD_DEBUG_Z_SLICES", "debug_z_slices"),
-                    (
-                        "CLUSTERED_FORWARD_DEBUG_CLUSTER_LIGHT_COMPLEXITY",
-                        "debug_cluster_light_complexity",
-                    ),
-                    (
-                        "CLUSTERED_FORWARD_DEBUG_CLUSTER_COHERENCY",
-                        "debug_cluster_coherency",
-                    ),
-                ],
-                "none",
-            ),
-            (
-                &[("DIRECTIONAL_LIGHT_SHADOW_MAP_DEBUG_CASCADES", "some")],
-                "none",
-            ),
-        ]
-    }
-
-    fn constants() -> EntryPointConstants {
-        &["MAX_DIRECTIONAL_LIGHTS", "MAX_CASCADES_PER_LIGHT"]
-    }
+    // `AVAILABLE_STORAGE_BUFFER_BINDINGS` inserts a storage / uniform parameter when present
+    crate::metadata_entry_point!("pbr::entry_points::fragment", 0..=1);
 
     fn permutation(shader_defs: &Vec<ShaderDefVal>) -> Vec<String> {
-        let mut permutation = vec![];
-
-        for (defined, undefined) in Self::parameters().iter() {
-            if let Some(mapping) = defined.iter().find_map(|(def, mapping)| {
-                if shader_defs.contains(&ShaderDefVal::Bool(def.to_string(), true)) {
-                    Some(mapping)
-                } else {
-                    None
-                }
-            }) {
-                permutation.push(mapping.to_string());
-            } else {
-                permutation.push(undefined.to_string())
-            };
+        let mut permutation = metadata_permutation(Self::NAME, shader_defs);
+        if permutation.is_empty() {
+            // Metadata not yet registered
+            return permutation;
         }
 
         if let Some(ge) = shader_defs.iter().find_map(|def| match def {
@@ -114,6 +97,13 @@ impl EntryPoint for PbrFragment {
 
         permutation
     }
+
+    fn consumed_shader_defs() -> Vec<&'static str> {
+        let mut shader_defs = mapped_shader_defs(Self::parameters(), Self::constants());
+        shader_defs.push("AVAILABLE_STORAGE_BUFFER_BINDINGS");
+        shader_defs.extend(MESH_PIPELINE_IGNORED_SHADER_DEFS);
+        shader_defs
+    }
 }
 
 /// `StandardMaterial` implementation
@@ -121,3 +111,48 @@ impl RustGpuMaterial for StandardMaterial {
     type Vertex = MeshVertex;
     type Fragment = PbrFragment;
 }
+
+#[cfg(feature = "hot-rebuild")]
+impl crate::prelude::DiscoverMaterial for StandardMaterial {
+    fn discovery_shader_defs(
+        &self,
+        _mesh_key: bevy::pbr::MeshPipelineKey,
+        _layout: &bevy::render::mesh::MeshVertexBufferLayout,
+        _vertex_shader_defs: &mut Vec<ShaderDefVal>,
+        fragment_shader_defs: &mut Vec<ShaderDefVal>,
+    ) {
+        if self.normal_map_texture.is_some() {
+            fragment_shader_defs.push("STANDARDMATERIAL_NORMAL_MAP".into());
+        }
+    }
+
+    fn discovery_bind_group_data(&self) -> Option<Self::Data> {
+        Some(self.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::ShaderDefVal;
+
+    use crate::prelude::EntryPoint;
+
+    use super::PbrFragment;
+
+    #[test]
+    fn pbr_fragment_permutations_match_arity() {
+        let arity = PbrFragment::arity();
+        assert_eq!(arity, 13..=14);
+
+        for shader_defs in [
+            vec![],
+            vec![ShaderDefVal::UInt(
+                "AVAILABLE_STORAGE_BUFFER_BINDINGS".into(),
+                3,
+            )],
+        ] {
+            let permutation = PbrFragment::permutation(&shader_defs);
+            assert!(arity.contains(&permutation.len()), "{permutation:?}");
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Command-line tooling for `bevy-rust-gpu` artifacts and entry point exports.
+
+use std::{collections::BTreeMap, path::PathBuf, process::ExitCode};
+
+use bevy_rust_gpu::prelude::{
+    builder_output_from_bytes, builder_output_to_bytes, demangle_entry_point_name, file_reader,
+    missing_entry_points, DemangledEntryPoint, EntryPointDiff, EntryPoints, ParameterLabels,
+    RustGpuBuilderModules, RustGpuBuilderOutput,
+};
+use serde::Serialize;
+
+const USAGE: &str = "\
+Usage: bevy-rust-gpu <COMMAND>
+
+Commands:
+  check <ENTRY_POINTS> <ARTIFACT>  Verify that ARTIFACT provides every entry point in ENTRY_POINTS
+  diff <FROM> <TO>                 Report permutations added, removed and changed between two exports
+  inspect <ARTIFACT> [--json]      Describe the entry points and modules of ARTIFACT
+  convert <INPUT> <OUTPUT>         Convert an artifact between the .json and .msgpack containers";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let result = match args.as_slice() {
+        ["check", entry_points, artifact] => check(entry_points, artifact),
+        ["diff", from, to] => diff(from, to),
+        ["inspect", artifact] => inspect(artifact, false),
+        ["inspect", artifact, "--json"] => inspect(artifact, true),
+        ["convert", input, output] => convert(input, output),
+        _ => {
+            eprintln!("{USAGE:}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {e:}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+type CommandResult = Result<ExitCode, Box<dyn std::error::Error>>;
+
+fn load_artifact(path: &str) -> Result<RustGpuBuilderOutput, Box<dyn std::error::Error>> {
+    let bytes = std::fs::read(path).map_err(|e| format!("{path:}: {e:}"))?;
+    Ok(builder_output_from_bytes(path, &bytes).map_err(|e| format!("{path:}: {e:}"))?)
+}
+
+fn load_entry_points(path: &str) -> Result<EntryPoints, Box<dyn std::error::Error>> {
+    Ok(file_reader(PathBuf::from(path))
+        .map_err(|e| format!("{path:}: {e:}"))?
+        .ok_or_else(|| format!("{path:}: File not found"))?)
+}
+
+/// Lists any entry points required by the manifest that are missing from the artifact,
+/// failing if there are any.
+fn check(entry_points: &str, artifact: &str) -> CommandResult {
+    let manifest = load_entry_points(entry_points)?;
+
+    let output = load_artifact(artifact)?;
+
+    let missing = missing_entry_points(&manifest, &output);
+    if missing.is_empty() {
+        println!("All entry points present");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    eprintln!(
+        "Missing {:} entry point(s) from {artifact:}:",
+        missing.len()
+    );
+    for name in missing {
+        eprintln!("  {name:}");
+    }
+
+    Ok(ExitCode::FAILURE)
+}
+
+/// Parameter labels for the entry points known to this crate.
+fn parameter_labels() -> ParameterLabels {
+    #[allow(unused_mut)]
+    let mut labels = ParameterLabels::default();
+
+    #[cfg(feature = "bevy-pbr-rust")]
+    {
+        use bevy_rust_gpu::bevy_pbr_rust::{MeshFragment, MeshVertex, PbrFragment};
+        labels.insert::<MeshVertex>();
+        labels.insert::<MeshFragment>();
+        labels.insert::<PbrFragment>();
+    }
+
+    labels
+}
+
+/// Prints the permutations that differ between two exports, failing if there are any.
+fn diff(from: &str, to: &str) -> CommandResult {
+    let diff = EntryPointDiff::new(&load_entry_points(from)?, &load_entry_points(to)?);
+
+    if diff.is_empty() {
+        println!("No differences");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    print!("{:}", diff.describe(&parameter_labels()));
+    Ok(ExitCode::FAILURE)
+}
+
+/// Serializable description of an artifact.
+#[derive(Serialize)]
+struct ArtifactSummary {
+    container: &'static str,
+    /// Size in bytes of each module, keyed by name.
+    modules: BTreeMap<String, usize>,
+    entry_points: Vec<EntryPointSummary>,
+}
+
+#[derive(Serialize)]
+struct EntryPointSummary {
+    name: String,
+    base: String,
+    components: Vec<String>,
+    /// Name of the module containing this entry point, if any.
+    module: Option<String>,
+}
+
+/// Name used for the module of a single-module artifact.
+const SINGLE_MODULE: &str = "single";
+
+impl From<&RustGpuBuilderOutput> for ArtifactSummary {
+    fn from(output: &RustGpuBuilderOutput) -> Self {
+        let (container, modules) = match &output.modules {
+            RustGpuBuilderModules::Single(module) => (
+                "Single",
+                BTreeMap::from([(SINGLE_MODULE.to_string(), module.len())]),
+            ),
+            RustGpuBuilderModules::Multi(modules) => (
+                "Multi",
+                modules
+                    .iter()
+                    .map(|(name, module)| (name.clone(), module.len()))
+                    .collect(),
+            ),
+        };
+
+        let entry_points = output
+            .entry_points
+            .iter()
+            .map(|name| {
+                let DemangledEntryPoint {
+                    name: base,
+                    components,
+                } = demangle_entry_point_name(name);
+
+                let module = match &output.modules {
+                    RustGpuBuilderModules::Single(_) => Some(SINGLE_MODULE.to_string()),
+                    RustGpuBuilderModules::Multi(modules) => {
+                        modules.contains_key(name).then(|| name.clone())
+                    }
+                };
+
+                EntryPointSummary {
+                    name: name.clone(),
+                    base,
+                    components,
+                    module,
+                }
+            })
+            .collect();
+
+        ArtifactSummary {
+            container,
+            modules,
+            entry_points,
+        }
+    }
+}
+
+/// Prints the container type, modules and entry points of an artifact.
+fn inspect(artifact: &str, json: bool) -> CommandResult {
+    let output = load_artifact(artifact)?;
+    let summary = ArtifactSummary::from(&output);
+
+    if json {
+        println!("{:}", serde_json::to_string_pretty(&summary)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    println!("Container: {:}", summary.container);
+
+    println!("Modules ({:}):", summary.modules.len());
+    for (name, size) in summary.modules.iter() {
+        println!("  {name:} ({size:} bytes)");
+    }
+
+    println!("Entry points ({:}):", summary.entry_points.len());
+    for entry_point in summary.entry_points.iter() {
+        let demangled = DemangledEntryPoint {
+            name: entry_point.base.clone(),
+            components: entry_point.components.clone(),
+        };
+
+        match &entry_point.module {
+            Some(module) if module == &entry_point.name => println!("  {demangled:}"),
+            Some(module) => println!("  {demangled:} -> {module:}"),
+            None => println!("  {demangled:} -> missing module"),
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Re-encodes an artifact in the container format matching the output extension.
+fn convert(input: &str, output: &str) -> CommandResult {
+    let artifact = load_artifact(input)?;
+    let bytes =
+        builder_output_to_bytes(output, &artifact).map_err(|e| format!("{output:}: {e:}"))?;
+    std::fs::write(output, bytes).map_err(|e| format!("{output:}: {e:}"))?;
+    println!("Converted {input:} to {output:}");
+    Ok(ExitCode::SUCCESS)
+}
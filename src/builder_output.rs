@@ -1,8 +1,16 @@
-use std::{collections::BTreeMap, sync::RwLock};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    hash::{Hash, Hasher},
+    sync::{Mutex, RwLock},
+};
 
-use bevy::prelude::{
-    default, AssetEvent, Assets, CoreSet, Deref, DerefMut, EventReader, Handle, IntoSystemConfig,
-    Plugin, Res, ResMut, Shader,
+use bevy::{
+    asset::HandleId,
+    prelude::{
+        default, error, info, warn, AddAsset, AssetEvent, Assets, CoreSet, Deref, DerefMut,
+        EventReader, Handle, IntoSystemConfig, Plugin, Res, ResMut, Shader,
+    },
+    reflect::TypeUuid,
 };
 use once_cell::sync::Lazy;
 use rust_gpu_builder_shared::RustGpuBuilderOutput;
@@ -10,10 +18,16 @@ use rust_gpu_builder_shared::RustGpuBuilderOutput;
 /// Static container for `RustGpuArtifacts` to allow access from `Material::specialize`
 pub static RUST_GPU_ARTIFACTS: Lazy<RwLock<RustGpuArtifacts>> = Lazy::new(default);
 
+/// Static queue of modules requested from `Material::specialize`, awaiting shader creation
+static PENDING_MODULES: Lazy<Mutex<Vec<PendingModule>>> = Lazy::new(default);
+
 pub struct BuilderOutputPlugin;
 
 impl Plugin for BuilderOutputPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
+        // Register the asset type regardless of loader features to support embedded outputs
+        app.add_asset::<RustGpuBuilderOutput>();
+
         #[cfg(feature = "json")]
         app.add_plugin(bevy_common_assets::json::JsonAssetPlugin::<
             RustGpuBuilderOutput,
@@ -24,7 +38,21 @@ impl Plugin for BuilderOutputPlugin {
             RustGpuBuilderOutput,
         >::new(&["rust-gpu.msgpack"]));
 
+        #[cfg(feature = "wgsl")]
+        app.add_event::<crate::wgsl::TranspileFailed>();
+
+        #[cfg(feature = "wgsl")]
+        app.add_event::<crate::wgsl::ModuleTranspiled>();
+
+        app.init_resource::<crate::prelude::RustGpuLibrary>();
+
         app.add_system(builder_output_events.in_base_set(CoreSet::PreUpdate));
+        app.add_system(
+            load_pending_modules_system
+                .in_base_set(CoreSet::PreUpdate)
+                .after(builder_output_events),
+        );
+        app.add_system(crate::prelude::library_changed_system.in_base_set(CoreSet::PreUpdate));
     }
 }
 
@@ -33,22 +61,165 @@ impl Plugin for BuilderOutputPlugin {
 pub enum RustGpuModules {
     /// Contains a single unnamed shader.
     Single(Handle<Shader>),
-    /// Contains multiple named shaders.
-    Multi(BTreeMap<String, Handle<Shader>>),
+    /// Contains multiple named modules, whose shaders are created on first use.
+    Multi(BTreeMap<String, RustGpuModule>),
+}
+
+/// Lazily-loaded module from a multi-module artifact.
+///
+/// Raw SPIR-V is not duplicated here, but read from the [`RustGpuBuilderOutput`] asset when loading.
+#[derive(Debug, Clone)]
+pub enum RustGpuModule {
+    /// Not yet loaded, and queued for shader creation when first resolved.
+    Unloaded,
+    /// Loaded into a shader asset.
+    Loaded(Handle<Shader>),
+    /// Failed to load.
+    Failed,
+}
+
+/// A module requested by specialization, awaiting shader creation.
+#[derive(Debug, PartialEq, Eq)]
+struct PendingModule {
+    artifact: Handle<RustGpuBuilderOutput>,
+    entry_point: String,
 }
 
 /// Asset containing loaded rust-gpu shaders and entry point metadata.
 #[derive(Debug, Clone)]
 pub struct RustGpuArtifact {
     pub entry_points: Vec<String>,
+    /// Maps entry point names to their names within the loaded modules, where they differ.
+    pub entry_point_names: BTreeMap<String, String>,
     pub modules: RustGpuModules,
 }
 
+impl RustGpuArtifact {
+    /// Returns the shader containing `entry_point` and its name within that shader,
+    /// or `None` if this artifact does not contain it.
+    ///
+    /// If the containing module has yet to be loaded, it is queued for shader creation,
+    /// and a weak handle to the shader-to-be is returned.
+    /// With the `wgsl` feature, `None` is returned instead until the module is loaded,
+    /// as its entry point names are not known beforehand.
+    pub fn resolve(
+        &self,
+        handle: &Handle<RustGpuBuilderOutput>,
+        entry_point: &str,
+    ) -> Option<(Handle<Shader>, String)> {
+        if !self
+            .entry_points
+            .iter()
+            .any(|candidate| candidate == entry_point)
+        {
+            return None;
+        }
+
+        let shader = match &self.modules {
+            RustGpuModules::Single(single) => single.clone(),
+            RustGpuModules::Multi(multi) => match multi.get(entry_point) {
+                Some(RustGpuModule::Loaded(shader)) => shader.clone(),
+                Some(RustGpuModule::Failed) => return None,
+                Some(RustGpuModule::Unloaded) => {
+                    let pending = PendingModule {
+                        artifact: handle.clone_weak(),
+                        entry_point: entry_point.to_string(),
+                    };
+
+                    let mut queue = PENDING_MODULES.lock().unwrap();
+                    if !queue.contains(&pending) {
+                        queue.push(pending);
+                    }
+
+                    // WGSL entry point names are only known once the module is transpiled,
+                    // so wait for it rather than specializing against a guessed name
+                    if cfg!(feature = "wgsl") {
+                        info!("Deferring entry point {entry_point:} until its module is loaded.");
+                        return None;
+                    }
+
+                    Handle::weak(module_shader_id(handle, entry_point))
+                }
+                None => {
+                    warn!("Missing handle for entry point {entry_point:}.");
+                    return None;
+                }
+            },
+        };
+
+        let name = self
+            .entry_point_names
+            .get(entry_point)
+            .cloned()
+            .unwrap_or_else(|| entry_point.to_string());
+
+        Some((shader, name))
+    }
+}
+
 #[derive(Debug, Default, Clone, Deref, DerefMut)]
 pub struct RustGpuArtifacts {
     pub artifacts: BTreeMap<Handle<RustGpuBuilderOutput>, RustGpuArtifact>,
 }
 
+#[cfg(not(feature = "wgsl"))]
+type LoadModuleError = std::convert::Infallible;
+
+#[cfg(feature = "wgsl")]
+type LoadModuleError = crate::wgsl::TranspileError;
+
+/// Creates a [`Shader`] from a SPIR-V module, alongside any entry point name remappings.
+#[cfg(not(feature = "wgsl"))]
+fn load_module(
+    _artifact: &Handle<RustGpuBuilderOutput>,
+    module: &[u8],
+) -> Result<(Shader, BTreeMap<String, String>), LoadModuleError> {
+    Ok((Shader::from_spirv(module.to_vec()), default()))
+}
+
+/// Creates a [`Shader`] from a SPIR-V module by transpiling it to WGSL,
+/// alongside the WGSL names of its entry points.
+#[cfg(feature = "wgsl")]
+fn load_module(
+    artifact: &Handle<RustGpuBuilderOutput>,
+    module: &[u8],
+) -> Result<(Shader, BTreeMap<String, String>), LoadModuleError> {
+    crate::wgsl::spirv_to_wgsl_cached(artifact, module).map(|transpiled| {
+        (
+            Shader::from_wgsl(transpiled.source),
+            transpiled.entry_points,
+        )
+    })
+}
+
+/// Returns a stable shader asset ID for the module containing `entry_point` in the given artifact.
+fn module_shader_id(artifact: &Handle<RustGpuBuilderOutput>, entry_point: &str) -> HandleId {
+    let mut hasher = DefaultHasher::new();
+    artifact.id().hash(&mut hasher);
+    entry_point.hash(&mut hasher);
+    HandleId::new(Shader::TYPE_UUID, hasher.finish())
+}
+
+/// Logs an entry point whose module failed to load, and sends a corresponding event if applicable.
+#[cfg_attr(not(feature = "wgsl"), allow(unused_variables))]
+fn report_failure(
+    handle: &Handle<RustGpuBuilderOutput>,
+    entry_point: &str,
+    error: &LoadModuleError,
+    #[cfg(feature = "wgsl")] transpile_failed: &mut bevy::prelude::EventWriter<
+        crate::wgsl::TranspileFailed,
+    >,
+) {
+    error!("Failed to load module for entry point {entry_point:}: {error:}");
+
+    #[cfg(feature = "wgsl")]
+    transpile_failed.send(crate::wgsl::TranspileFailed {
+        handle: handle.clone_weak(),
+        entry_point: entry_point.to_string(),
+        error: error.clone(),
+    });
+}
+
 /// [`RustGpuBuilderOutput`] asset event handler.
 ///
 /// Handles loading shader assets, maintaining static material data, and respecializing materials on reload.
@@ -56,29 +227,72 @@ pub fn builder_output_events(
     mut builder_output_events: EventReader<AssetEvent<RustGpuBuilderOutput>>,
     builder_outputs: Res<Assets<RustGpuBuilderOutput>>,
     mut shaders: ResMut<Assets<Shader>>,
+    #[cfg(feature = "wgsl")] mut transpile_failed: bevy::prelude::EventWriter<
+        crate::wgsl::TranspileFailed,
+    >,
 ) {
     for event in builder_output_events.iter() {
         if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
-            let asset = builder_outputs.get(handle).unwrap().clone();
+            let asset = builder_outputs.get(handle).unwrap();
 
-            // Create a `RustGpuArtifact` from the affected asset
-            let artifact = match asset.modules {
-                rust_gpu_builder_shared::RustGpuBuilderModules::Single(ref single) => {
-                    let shader = shaders.add(Shader::from_spirv(single.clone()));
-                    RustGpuArtifact {
-                        entry_points: asset.entry_points,
-                        modules: RustGpuModules::Single(shader),
+            // Drop cached transpilations of modules that no longer belong to this artifact
+            #[cfg(feature = "wgsl")]
+            if let AssetEvent::Modified { .. } = event {
+                crate::wgsl::evict_transpiled(
+                    handle,
+                    match &asset.modules {
+                        rust_gpu_builder_shared::RustGpuBuilderModules::Single(single) => {
+                            vec![single.as_slice()]
+                        }
+                        rust_gpu_builder_shared::RustGpuBuilderModules::Multi(multi) => {
+                            multi.values().map(Vec::as_slice).collect()
+                        }
+                    },
+                );
+            }
+
+            let mut entry_points = asset.entry_points.clone();
+            let mut entry_point_names = BTreeMap::default();
+
+            // Create a shader asset for single-module artifacts,
+            // deferring multi-module shaders until their entry points are first used
+            let modules = match &asset.modules {
+                rust_gpu_builder_shared::RustGpuBuilderModules::Single(single) => {
+                    match load_module(handle, single) {
+                        Ok((shader, names)) => {
+                            entry_point_names.extend(names);
+                            RustGpuModules::Single(shaders.add(shader))
+                        }
+                        Err(error) => {
+                            // Report the artifact's entry points, and treat them as missing
+                            for entry_point in entry_points.drain(..) {
+                                report_failure(
+                                    handle,
+                                    &entry_point,
+                                    &error,
+                                    #[cfg(feature = "wgsl")]
+                                    &mut transpile_failed,
+                                );
+                            }
+                            RustGpuModules::Single(default())
+                        }
                     }
                 }
-                rust_gpu_builder_shared::RustGpuBuilderModules::Multi(multi) => RustGpuArtifact {
-                    entry_points: asset.entry_points,
-                    modules: RustGpuModules::Multi(
+                rust_gpu_builder_shared::RustGpuBuilderModules::Multi(multi) => {
+                    RustGpuModules::Multi(
                         multi
-                            .into_iter()
-                            .map(|(k, module)| (k.clone(), shaders.add(Shader::from_spirv(module))))
+                            .keys()
+                            .map(|entry_point| (entry_point.clone(), RustGpuModule::Unloaded))
                             .collect(),
-                    ),
-                },
+                    )
+                }
+            };
+
+            // Create a `RustGpuArtifact` from the affected asset
+            let artifact = RustGpuArtifact {
+                entry_points,
+                entry_point_names,
+                modules,
             };
 
             // Emplace it in static storage
@@ -90,7 +304,90 @@ pub fn builder_output_events(
 
         // On remove, remove the corresponding artifact from static storage
         if let AssetEvent::Removed { handle } = event {
+            #[cfg(feature = "wgsl")]
+            crate::wgsl::evict_transpiled(handle, []);
+
             RUST_GPU_ARTIFACTS.write().unwrap().remove(handle);
         }
     }
 }
+
+/// System used to create shader assets for modules requested during specialization.
+///
+/// Modules are loaded without holding the artifact lock, which is only taken to record the result
+/// alongside any entry point names discovered while loading.
+pub fn load_pending_modules_system(
+    builder_outputs: Res<Assets<RustGpuBuilderOutput>>,
+    mut shaders: ResMut<Assets<Shader>>,
+    #[cfg(feature = "wgsl")] mut transpile_failed: bevy::prelude::EventWriter<
+        crate::wgsl::TranspileFailed,
+    >,
+    #[cfg(feature = "wgsl")] mut module_transpiled: bevy::prelude::EventWriter<
+        crate::wgsl::ModuleTranspiled,
+    >,
+) {
+    let pending = std::mem::take(&mut *PENDING_MODULES.lock().unwrap());
+
+    for PendingModule {
+        artifact,
+        entry_point,
+    } in pending
+    {
+        // The artifact may have been removed since the module was requested
+        let Some(rust_gpu_builder_shared::RustGpuBuilderOutput {
+            modules: rust_gpu_builder_shared::RustGpuBuilderModules::Multi(multi),
+            ..
+        }) = builder_outputs.get(&artifact)
+        else {
+            continue;
+        };
+
+        let Some(module) = multi.get(&entry_point) else {
+            continue;
+        };
+
+        let (loaded, names) = match load_module(&artifact, module) {
+            Ok((loaded, names)) => (
+                RustGpuModule::Loaded(
+                    shaders.set(module_shader_id(&artifact, &entry_point), loaded),
+                ),
+                names,
+            ),
+            Err(error) => {
+                report_failure(
+                    &artifact,
+                    &entry_point,
+                    &error,
+                    #[cfg(feature = "wgsl")]
+                    &mut transpile_failed,
+                );
+                (RustGpuModule::Failed, default())
+            }
+        };
+
+        let mut artifacts = RUST_GPU_ARTIFACTS.write().unwrap();
+        let Some(RustGpuArtifact {
+            entry_point_names,
+            modules: RustGpuModules::Multi(multi),
+            ..
+        }) = artifacts.get_mut(&artifact)
+        else {
+            continue;
+        };
+
+        let Some(module @ RustGpuModule::Unloaded) = multi.get_mut(&entry_point) else {
+            continue;
+        };
+
+        #[cfg(feature = "wgsl")]
+        if let RustGpuModule::Loaded(_) = loaded {
+            module_transpiled.send(crate::wgsl::ModuleTranspiled {
+                handle: artifact.clone_weak(),
+                entry_point: entry_point.clone(),
+            });
+        }
+
+        *module = loaded;
+        entry_point_names.extend(names);
+    }
+}
@@ -0,0 +1,300 @@
+//! Data-driven counterpart to the [`EntryPoint`] trait.
+
+use std::{any::TypeId, ops::RangeInclusive};
+
+use bevy::render::render_resource::ShaderDefVal;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{
+    mangle_constants, mangle_entry_point_name, EntryPoint, EntryPointContext, EntryPointTypes,
+};
+
+/// A single positional parameter of a [`DynamicEntryPoint`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DynamicEntryPointParameter {
+    /// Shader def / mapping pairs, in priority order.
+    pub defined: Vec<(String, String)>,
+    /// Mapping used when none of the shader defs in `defined` are present.
+    pub undefined: String,
+}
+
+/// Specialization hooks of the [`EntryPoint`] type a [`DynamicEntryPoint`] was captured from.
+///
+/// Compared, hashed and ordered by entry point type.
+#[derive(Copy, Clone)]
+pub struct DynamicEntryPointHooks {
+    entry_point: TypeId,
+    permutation_with: fn(&EntryPointContext) -> Vec<String>,
+    types_with: fn(&EntryPointContext) -> EntryPointTypes,
+    arity: fn() -> RangeInclusive<usize>,
+    consumed_shader_defs: fn() -> Vec<&'static str>,
+}
+
+impl DynamicEntryPointHooks {
+    /// Captures the hooks of entry point type `E`.
+    pub fn of<E: EntryPoint>() -> Self {
+        DynamicEntryPointHooks {
+            entry_point: TypeId::of::<E>(),
+            permutation_with: E::permutation_with,
+            types_with: E::types_with,
+            arity: E::arity,
+            consumed_shader_defs: E::consumed_shader_defs,
+        }
+    }
+}
+
+impl std::fmt::Debug for DynamicEntryPointHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynamicEntryPointHooks")
+            .field("entry_point", &self.entry_point)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for DynamicEntryPointHooks {
+    fn eq(&self, other: &Self) -> bool {
+        self.entry_point == other.entry_point
+    }
+}
+
+impl Eq for DynamicEntryPointHooks {}
+
+impl PartialOrd for DynamicEntryPointHooks {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DynamicEntryPointHooks {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.entry_point.cmp(&other.entry_point)
+    }
+}
+
+impl std::hash::Hash for DynamicEntryPointHooks {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.entry_point.hash(state);
+    }
+}
+
+/// An entry point described by data rather than a Rust type.
+///
+/// Mirrors the associated items of [`EntryPoint`], so can be loaded from a serialized description:
+///
+/// ```json
+/// {
+///   "name": "pbr::entry_points::fragment",
+///   "parameters": [{ "defined": [["VERTEX_UVS", "some"]], "undefined": "none" }],
+///   "constants": ["MAX_DIRECTIONAL_LIGHTS"]
+/// }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DynamicEntryPoint {
+    /// The entry point's base function name, including module path.
+    pub name: String,
+    #[serde(default)]
+    pub parameters: Vec<DynamicEntryPointParameter>,
+    #[serde(default)]
+    pub constants: Vec<String>,
+    #[serde(default)]
+    pub types: Vec<(String, String)>,
+    /// Hooks of the [`EntryPoint`] this was captured from via [`DynamicEntryPoint::of`], if any.
+    ///
+    /// Data-only entry points have no hooks, so specialize from shader defs alone.
+    #[serde(skip)]
+    pub hooks: Option<DynamicEntryPointHooks>,
+}
+
+impl DynamicEntryPoint {
+    /// Constructs an entry point with no parameters, constants or types,
+    /// which will always build to `name`.
+    pub fn new(name: impl Into<String>) -> Self {
+        DynamicEntryPoint {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Captures the metadata and specialization hooks of entry point type `E`.
+    pub fn of<E: EntryPoint>() -> Self {
+        DynamicEntryPoint {
+            name: E::NAME.to_string(),
+            parameters: E::parameters()
+                .iter()
+                .map(|(defined, undefined)| DynamicEntryPointParameter {
+                    defined: defined
+                        .iter()
+                        .map(|(def, mapping)| (def.to_string(), mapping.to_string()))
+                        .collect(),
+                    undefined: undefined.to_string(),
+                })
+                .collect(),
+            constants: E::constants().iter().map(ToString::to_string).collect(),
+            types: E::types(),
+            hooks: Some(DynamicEntryPointHooks::of::<E>()),
+        }
+    }
+
+    /// Constructs a permutation set from the provided shader defs, as per [`EntryPoint::permutation`].
+    pub fn permutation(&self, shader_defs: &[ShaderDefVal]) -> Vec<String> {
+        self.parameters
+            .iter()
+            .map(|parameter| {
+                parameter
+                    .defined
+                    .iter()
+                    .find(|(def, _)| shader_defs.contains(&ShaderDefVal::Bool(def.clone(), true)))
+                    .map(|(_, mapping)| mapping)
+                    .unwrap_or(&parameter.undefined)
+                    .clone()
+            })
+            .collect()
+    }
+
+    /// Filters the provided shader defs down to this entry point's constants,
+    /// as per [`EntryPoint::filter_constants`].
+    pub fn filter_constants(&self, shader_defs: &[ShaderDefVal]) -> Vec<ShaderDefVal> {
+        shader_defs
+            .iter()
+            .filter(|def| match def {
+                ShaderDefVal::Bool(key, _)
+                | ShaderDefVal::Int(key, _)
+                | ShaderDefVal::UInt(key, _) => self.constants.contains(key),
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Shader defs read by this entry point, as per [`EntryPoint::consumed_shader_defs`].
+    pub fn consumed_shader_defs(&self) -> Vec<&str> {
+        match self.hooks {
+            Some(hooks) => (hooks.consumed_shader_defs)(),
+            None => self
+                .parameters
+                .iter()
+                .flat_map(|parameter| parameter.defined.iter().map(|(def, _)| def.as_str()))
+                .chain(self.constants.iter().map(String::as_str))
+                .collect(),
+        }
+    }
+
+    /// Build an entry point name from the provided shader defs, as per [`EntryPoint::build`].
+    pub fn build(&self, shader_defs: &[ShaderDefVal]) -> String {
+        mangle_entry_point_name(
+            &self.name,
+            self.permutation(shader_defs),
+            mangle_constants(self.filter_constants(shader_defs)),
+            self.types.iter().cloned(),
+        )
+    }
+
+    /// Constructs a permutation set from the full specialization inputs, as per [`EntryPoint::permutation_with`].
+    pub fn permutation_with(&self, context: &EntryPointContext) -> Vec<String> {
+        match self.hooks {
+            Some(hooks) => (hooks.permutation_with)(context),
+            None => self.permutation(context.shader_defs),
+        }
+    }
+
+    /// Constructs the generic type instantiation from the full specialization inputs,
+    /// as per [`EntryPoint::types_with`].
+    pub fn types_with(&self, context: &EntryPointContext) -> EntryPointTypes {
+        match self.hooks {
+            Some(hooks) => (hooks.types_with)(context),
+            None => self.types.clone(),
+        }
+    }
+
+    /// Build an entry point name from the full specialization inputs, as per [`EntryPoint::build_with`].
+    pub fn build_with(&self, context: &EntryPointContext) -> String {
+        mangle_entry_point_name(
+            &self.name,
+            self.permutation_with(context),
+            mangle_constants(self.filter_constants(context.shader_defs)),
+            self.types_with(context),
+        )
+    }
+
+    /// Range of permutation lengths this entry point may produce, as per [`EntryPoint::arity`].
+    pub fn arity(&self) -> RangeInclusive<usize> {
+        match self.hooks {
+            Some(hooks) => (hooks.arity)(),
+            None => self.parameters.len()..=self.parameters.len(),
+        }
+    }
+}
+
+impl From<&str> for DynamicEntryPoint {
+    fn from(name: &str) -> Self {
+        DynamicEntryPoint::new(name)
+    }
+}
+
+impl From<String> for DynamicEntryPoint {
+    fn from(name: String) -> Self {
+        DynamicEntryPoint::new(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        pbr::MeshPipelineKey,
+        prelude::Mesh,
+        render::{render_resource::PrimitiveTopology, render_resource::ShaderDefVal},
+    };
+
+    use crate::prelude::{
+        EntryPoint, EntryPointContext, EntryPointMeshKey, EntryPointParameters, EntryPointTypes,
+    };
+
+    use super::DynamicEntryPoint;
+
+    enum Hooked {}
+
+    impl EntryPoint for Hooked {
+        const NAME: &'static str = "hooked";
+
+        fn parameters() -> EntryPointParameters {
+            &[(&[("VERTEX_UVS", "some")], "none")]
+        }
+
+        fn permutation_with(context: &EntryPointContext) -> Vec<String> {
+            let mut permutation = Self::permutation(context.shader_defs);
+            permutation.push("hooked".to_string());
+            permutation
+        }
+
+        fn types_with(_context: &EntryPointContext) -> EntryPointTypes {
+            vec![("T".to_string(), "f32".to_string())]
+        }
+
+        fn arity() -> std::ops::RangeInclusive<usize> {
+            2..=2
+        }
+    }
+
+    #[test]
+    fn captured_entry_points_keep_hooks() {
+        let layout = Mesh::new(PrimitiveTopology::TriangleList).get_mesh_vertex_buffer_layout();
+        let shader_defs = vec![ShaderDefVal::Bool("VERTEX_UVS".into(), true)];
+        let context = EntryPointContext {
+            shader_defs: &shader_defs,
+            layout: &layout,
+            mesh_key: EntryPointMeshKey::Mesh(MeshPipelineKey::NONE),
+            bind_group_data: None,
+        };
+
+        let captured = DynamicEntryPoint::of::<Hooked>();
+        assert_eq!(captured.build_with(&context), Hooked::build_with(&context));
+        assert_eq!(captured.arity(), 2..=2);
+
+        // Data-only entry points specialize from their tables alone
+        let data: DynamicEntryPoint =
+            serde_json::from_str(&serde_json::to_string(&captured).unwrap()).unwrap();
+        assert_eq!(data.hooks, None);
+        assert_eq!(data.permutation_with(&context), ["some"]);
+        assert_eq!(data.build_with(&context), data.build(&shader_defs));
+        assert_eq!(data.arity(), 1..=1);
+    }
+}
@@ -0,0 +1,131 @@
+//! Support for embedding `rust-gpu-builder` output into the binary at compile time.
+//!
+//! Embedded artifacts are registered under a stable handle, and require no filesystem access at runtime.
+
+use bevy::{
+    asset::HandleId,
+    prelude::{Assets, World},
+};
+use rust_gpu_builder_shared::RustGpuBuilderOutput;
+
+/// Error produced when deserializing an embedded [`RustGpuBuilderOutput`].
+#[derive(Debug)]
+pub enum EmbeddedBuilderOutputError {
+    /// The path extension did not correspond to a supported container format.
+    UnsupportedFormat(String),
+    /// The embedded bytes could not be deserialized as JSON.
+    Json(serde_json::Error),
+    /// The embedded bytes could not be deserialized as MessagePack.
+    #[cfg(feature = "msgpack")]
+    MsgPack(rmp_serde::decode::Error),
+    /// The builder output could not be serialized as MessagePack.
+    #[cfg(feature = "msgpack")]
+    MsgPackEncode(rmp_serde::encode::Error),
+}
+
+impl std::fmt::Display for EmbeddedBuilderOutputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmbeddedBuilderOutputError::UnsupportedFormat(path) => {
+                write!(f, "Unsupported builder output format: {path:}")
+            }
+            EmbeddedBuilderOutputError::Json(e) => write!(f, "JSON error: {e:}"),
+            #[cfg(feature = "msgpack")]
+            EmbeddedBuilderOutputError::MsgPack(e) => write!(f, "MessagePack error: {e:}"),
+            #[cfg(feature = "msgpack")]
+            EmbeddedBuilderOutputError::MsgPackEncode(e) => write!(f, "MessagePack error: {e:}"),
+        }
+    }
+}
+
+impl std::error::Error for EmbeddedBuilderOutputError {}
+
+/// Deserializes a [`RustGpuBuilderOutput`] from `bytes`,
+/// selecting the container format from the extension of `path`.
+pub fn builder_output_from_bytes(
+    path: &str,
+    bytes: &[u8],
+) -> Result<RustGpuBuilderOutput, EmbeddedBuilderOutputError> {
+    if path.ends_with(".json") {
+        return serde_json::from_slice(bytes).map_err(EmbeddedBuilderOutputError::Json);
+    }
+
+    #[cfg(feature = "msgpack")]
+    if path.ends_with(".msgpack") {
+        return rmp_serde::from_slice(bytes).map_err(EmbeddedBuilderOutputError::MsgPack);
+    }
+
+    Err(EmbeddedBuilderOutputError::UnsupportedFormat(
+        path.to_string(),
+    ))
+}
+
+/// Serializes a [`RustGpuBuilderOutput`],
+/// selecting the container format from the extension of `path`.
+pub fn builder_output_to_bytes(
+    path: &str,
+    output: &RustGpuBuilderOutput,
+) -> Result<Vec<u8>, EmbeddedBuilderOutputError> {
+    if path.ends_with(".json") {
+        return serde_json::to_vec(output).map_err(EmbeddedBuilderOutputError::Json);
+    }
+
+    #[cfg(feature = "msgpack")]
+    if path.ends_with(".msgpack") {
+        return rmp_serde::to_vec_named(output).map_err(EmbeddedBuilderOutputError::MsgPackEncode);
+    }
+
+    Err(EmbeddedBuilderOutputError::UnsupportedFormat(
+        path.to_string(),
+    ))
+}
+
+/// Deserializes an embedded [`RustGpuBuilderOutput`] and registers it under `handle`.
+///
+/// Panics if the embedded bytes are invalid, since they are fixed at compile time.
+///
+/// Usually invoked via [`load_embedded_builder_output`](crate::load_embedded_builder_output).
+pub fn embed_builder_output<H: Into<HandleId>>(
+    world: &mut World,
+    handle: H,
+    path: &'static str,
+    bytes: &[u8],
+) {
+    let output = builder_output_from_bytes(path, bytes)
+        .unwrap_or_else(|e| panic!("Failed to load embedded builder output {path:}: {e:}"));
+
+    world
+        .resource_mut::<Assets<RustGpuBuilderOutput>>()
+        .set_untracked(handle, output);
+}
+
+/// Embeds a `.rust-gpu.json` or `.rust-gpu.msgpack` file into the binary,
+/// and registers it as a [`RustGpuBuilderOutput`] asset with the provided stable handle.
+///
+/// The path is resolved relative to the calling file, as per [`include_bytes`].
+///
+/// Must be invoked after [`RustGpuPlugin`](crate::prelude::RustGpuPlugin) has been added.
+///
+/// ```ignore
+/// const SHADER_HANDLE: HandleUntyped =
+///     HandleUntyped::weak_from_u64(RustGpuBuilderOutput::TYPE_UUID, 1234567890);
+///
+/// load_embedded_builder_output!(app, SHADER_HANDLE, "shader.rust-gpu.msgpack");
+///
+/// let material = RustGpu::<MyMaterial> {
+///     vertex_shader: Some(SHADER_HANDLE.typed()),
+///     fragment_shader: Some(SHADER_HANDLE.typed()),
+///     ..default()
+/// };
+/// ```
+#[macro_export]
+macro_rules! load_embedded_builder_output {
+    ($app: ident, $handle: expr, $path_str: expr) => {{
+        $crate::embedded_builder_output::embed_builder_output(
+            &mut $app.world,
+            $handle,
+            $path_str,
+            include_bytes!($path_str),
+        );
+    }};
+}
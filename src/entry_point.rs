@@ -1,8 +1,12 @@
 //! Trait representation of a `rust-gpu` entry point.
 
-use std::collections::BTreeMap;
+use std::{any::Any, collections::BTreeMap, ops::RangeInclusive};
 
-use bevy::render::render_resource::ShaderDefVal;
+use bevy::{
+    pbr::MeshPipelineKey,
+    render::{mesh::MeshVertexBufferLayout, render_resource::ShaderDefVal},
+    sprite::Mesh2dPipelineKey,
+};
 
 /// An entry point name for use with the [`EntryPoint`] trait.
 pub type EntryPointName = &'static str;
@@ -17,6 +21,34 @@ pub type EntryPointConstants = &'static [&'static str];
 /// A set of entry point constants for use with the [`EntryPoint`] trait.
 pub type EntryPointTypes = Vec<(String, String)>;
 
+/// Mesh pipeline key of the material being specialized.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EntryPointMeshKey {
+    /// Specialized via [`Material`](bevy::prelude::Material).
+    Mesh(MeshPipelineKey),
+    /// Specialized via [`Material2d`](bevy::sprite::Material2d).
+    Mesh2d(Mesh2dPipelineKey),
+}
+
+/// Specialization inputs available to [`EntryPoint`] hooks.
+#[derive(Copy, Clone)]
+pub struct EntryPointContext<'a> {
+    /// Shader defs of the stage being specialized.
+    pub shader_defs: &'a Vec<ShaderDefVal>,
+    pub layout: &'a MeshVertexBufferLayout,
+    pub mesh_key: EntryPointMeshKey,
+    /// [`AsBindGroup::Data`](bevy::render::render_resource::AsBindGroup::Data) of the base material,
+    /// if available.
+    pub bind_group_data: Option<&'a dyn Any>,
+}
+
+impl<'a> EntryPointContext<'a> {
+    /// Returns the base material's bind group data if it is of type `T`.
+    pub fn bind_group_data<T: 'static>(&self) -> Option<&'a T> {
+        self.bind_group_data?.downcast_ref()
+    }
+}
+
 /// A `rust-gpu` entry point for use with [`RustGpuMaterial`](crate::rust_gpu_material::RustGpuMaterial).
 pub trait EntryPoint: 'static + Send + Sync {
     /// The entry point's base function name, including module path
@@ -74,63 +106,226 @@ pub trait EntryPoint: 'static + Send + Sync {
 
     /// Build an entry point name from the provided shader defs
     fn build(shader_defs: &Vec<ShaderDefVal>) -> String {
-        let constants = Self::filter_constants(shader_defs)
-            .into_iter()
-            .map(|def| {
-                (
-                    match &def {
-                        ShaderDefVal::Bool(key, _)
-                        | ShaderDefVal::Int(key, _)
-                        | ShaderDefVal::UInt(key, _) => key.clone(),
-                    },
-                    match &def {
-                        ShaderDefVal::Bool(value, _) => value.to_string(),
-                        ShaderDefVal::Int(_, value) => value.to_string(),
-                        ShaderDefVal::UInt(_, value) => value.to_string(),
-                    },
-                )
-            })
-            .collect::<BTreeMap<_, _>>();
+        mangle_entry_point_name(
+            Self::NAME,
+            Self::permutation(shader_defs),
+            mangle_constants(Self::filter_constants(shader_defs)),
+            Self::types(),
+        )
+    }
 
-        std::iter::once(Self::NAME.to_string())
-            .chain(
-                Self::permutation(shader_defs)
-                    .into_iter()
-                    .map(|variant| "__".to_string() + &variant),
-            )
-            .chain(
-                constants
-                    .into_iter()
-                    .map(|(key, value)| key + "_" + &value)
-                    .map(|variant| "__".to_string() + &variant),
-            )
-            .chain(
-                Self::types()
-                    .into_iter()
-                    .map(|(key, value)| {
-                        key.to_string().to_lowercase()
-                            + "_"
-                            + &value
-                                .to_string()
-                                .replace(" ", "")
-                                .replace("\n", "")
-                                .replace("<", "_")
-                                .replace(">", "_")
-                                .replace("[", "_")
-                                .replace("]", "_")
-                                .replace("(", "_")
-                                .replace(")", "_")
-                                .replace("::", "_")
-                                .replace(",", "_")
-                                .trim_end_matches("_")
-                                .to_lowercase()
-                    })
-                    .map(|variant| "__".to_string() + &variant),
+    /// Range of permutation lengths that [`EntryPoint::permutation`] and [`EntryPoint::permutation_with`] may produce.
+    ///
+    /// Used to detect exported permutations that no longer match this entry point.
+    /// Override alongside either method if it adds or removes parameters.
+    fn arity() -> RangeInclusive<usize> {
+        let parameters = Self::parameters().len();
+        parameters..=parameters
+    }
+
+    /// Shader defs read by this entry point, used to detect defs that it does not map.
+    ///
+    /// Defaults to those mapped by [`EntryPoint::parameters`] and [`EntryPoint::constants`].
+    /// Override alongside any hook that reads additional defs,
+    /// or to list defs that the entry point deliberately ignores.
+    fn consumed_shader_defs() -> Vec<&'static str> {
+        mapped_shader_defs(Self::parameters(), Self::constants())
+    }
+
+    /// Constructs a permutation set from the full specialization inputs.
+    ///
+    /// Override to select variants from the mesh key or material bind group data.
+    /// Defaults to [`EntryPoint::permutation`].
+    fn permutation_with(context: &EntryPointContext) -> Vec<String> {
+        Self::permutation(context.shader_defs)
+    }
+
+    /// Constructs the generic type instantiation from the full specialization inputs.
+    ///
+    /// Override to instantiate over different types per mesh layout, key or material.
+    /// Defaults to [`EntryPoint::types`].
+    fn types_with(_context: &EntryPointContext) -> EntryPointTypes {
+        Self::types()
+    }
+
+    /// Build an entry point name from the full specialization inputs.
+    ///
+    /// Used in place of [`EntryPoint::build`] during specialization.
+    fn build_with(context: &EntryPointContext) -> String {
+        mangle_entry_point_name(
+            Self::NAME,
+            Self::permutation_with(context),
+            mangle_constants(Self::filter_constants(context.shader_defs)),
+            Self::types_with(context),
+        )
+    }
+}
+
+/// Returns the shader defs mapped by `parameters` and `constants`.
+pub fn mapped_shader_defs(
+    parameters: EntryPointParameters,
+    constants: EntryPointConstants,
+) -> Vec<&'static str> {
+    parameters
+        .iter()
+        .flat_map(|(defined, _)| defined.iter().map(|(def, _)| *def))
+        .chain(constants.iter().copied())
+        .collect()
+}
+
+/// Converts filtered constants into the key-ordered name / value pairs used by [`mangle_entry_point_name`].
+pub(crate) fn mangle_constants(constants: Vec<ShaderDefVal>) -> BTreeMap<String, String> {
+    constants
+        .into_iter()
+        .map(|def| {
+            (
+                match &def {
+                    ShaderDefVal::Bool(key, _)
+                    | ShaderDefVal::Int(key, _)
+                    | ShaderDefVal::UInt(key, _) => key.clone(),
+                },
+                match &def {
+                    ShaderDefVal::Bool(value, _) => value.to_string(),
+                    ShaderDefVal::Int(_, value) => value.to_string(),
+                    ShaderDefVal::UInt(_, value) => value.to_string(),
+                },
             )
-            .collect::<String>()
+        })
+        .collect()
+}
+
+/// Builds a mangled entry point name from a base name and its permutation, constants and types,
+/// as per [`EntryPoint::build`].
+///
+/// Constants are expected to be in key order.
+pub fn mangle_entry_point_name(
+    name: &str,
+    permutation: impl IntoIterator<Item = String>,
+    constants: impl IntoIterator<Item = (String, String)>,
+    types: impl IntoIterator<Item = (String, String)>,
+) -> String {
+    std::iter::once(name.to_string())
+        .chain(
+            permutation
+                .into_iter()
+                .map(|variant| "__".to_string() + &variant),
+        )
+        .chain(
+            constants
+                .into_iter()
+                .map(|(key, value)| key + "_" + &value)
+                .map(|variant| "__".to_string() + &variant),
+        )
+        .chain(
+            types
+                .into_iter()
+                .map(|(key, value)| {
+                    key.to_lowercase()
+                        + "_"
+                        + &value
+                            .replace(" ", "")
+                            .replace("\n", "")
+                            .replace("<", "_")
+                            .replace(">", "_")
+                            .replace("[", "_")
+                            .replace("]", "_")
+                            .replace("(", "_")
+                            .replace(")", "_")
+                            .replace("::", "_")
+                            .replace(",", "_")
+                            .trim_end_matches("_")
+                            .to_lowercase()
+                })
+                .map(|variant| "__".to_string() + &variant),
+        )
+        .collect::<String>()
+}
+
+/// An entry point name split into its base name and mangled permutation components.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DemangledEntryPoint {
+    /// Base function name, including module path.
+    pub name: String,
+    /// Parameter, constant and type components, in mangled order.
+    pub components: Vec<String>,
+}
+
+impl std::fmt::Display for DemangledEntryPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:}", self.name)?;
+        if !self.components.is_empty() {
+            write!(f, " [{:}]", self.components.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a name produced by [`mangle_entry_point_name`] into its base name and components.
+///
+/// Components cannot be classified as parameters, constants or types without
+/// the corresponding [`EntryPoint`] metadata, so are returned as-is.
+/// Types whose sanitized form contains `__` will be split across multiple components.
+pub fn demangle_entry_point_name(name: &str) -> DemangledEntryPoint {
+    let mut components = name.split("__").map(str::to_string);
+    DemangledEntryPoint {
+        name: components.next().unwrap_or_default(),
+        components: components.collect(),
     }
 }
 
 impl EntryPoint for () {
     const NAME: &'static str = "";
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{demangle_entry_point_name, mangle_entry_point_name, DemangledEntryPoint};
+
+    #[test]
+    fn demangle_round_trips() {
+        let name = mangle_entry_point_name(
+            "pbr::entry_points::fragment",
+            ["some".to_string(), "none".to_string()],
+            [("MAX_LIGHTS".to_string(), "4".to_string())],
+            [("T".to_string(), "Vec<f32>".to_string())],
+        );
+        assert_eq!(
+            name,
+            "pbr::entry_points::fragment__some__none__MAX_LIGHTS_4__t_vec_f32"
+        );
+
+        let demangled = demangle_entry_point_name(&name);
+        assert_eq!(
+            demangled,
+            DemangledEntryPoint {
+                name: "pbr::entry_points::fragment".into(),
+                components: vec![
+                    "some".into(),
+                    "none".into(),
+                    "MAX_LIGHTS_4".into(),
+                    "t_vec_f32".into()
+                ],
+            }
+        );
+
+        assert_eq!(
+            std::iter::once(demangled.name.clone())
+                .chain(demangled.components.iter().cloned())
+                .collect::<Vec<_>>()
+                .join("__"),
+            name
+        );
+        assert_eq!(
+            demangled.to_string(),
+            "pbr::entry_points::fragment [some, none, MAX_LIGHTS_4, t_vec_f32]"
+        );
+    }
+
+    #[test]
+    fn demangle_unparameterized() {
+        let demangled = demangle_entry_point_name("mesh::entry_points::fragment");
+        assert_eq!(demangled.name, "mesh::entry_points::fragment");
+        assert!(demangled.components.is_empty());
+        assert_eq!(demangled.to_string(), "mesh::entry_points::fragment");
+    }
+}
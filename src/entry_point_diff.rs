@@ -0,0 +1,335 @@
+//! Compares two sets of exported entry points.
+//!
+//! Permutations are matched per shader, independent of export ordering,
+//! with positional parameters labelled by [`EntryPoint`] metadata where available.
+
+use std::collections::BTreeMap;
+
+use crate::prelude::{DynamicEntryPoint, EntryPoint, EntryPoints, Permutation};
+
+/// Human-readable labels for the positional parameters of known entry points, keyed by shader name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParameterLabels {
+    labels: BTreeMap<String, Vec<String>>,
+}
+
+impl ParameterLabels {
+    /// Registers labels for the parameters of `E`, derived from their shader defs.
+    pub fn insert<E: EntryPoint>(&mut self) {
+        self.labels.insert(
+            E::NAME.to_string(),
+            E::parameters()
+                .iter()
+                .map(|(defined, _)| {
+                    defined
+                        .iter()
+                        .map(|(def, _)| *def)
+                        .collect::<Vec<_>>()
+                        .join("|")
+                })
+                .collect(),
+        );
+    }
+
+    /// Registers labels for the parameters of a [`DynamicEntryPoint`], derived from their shader defs.
+    pub fn insert_dynamic(&mut self, entry_point: &DynamicEntryPoint) {
+        self.labels.insert(
+            entry_point.name.clone(),
+            entry_point
+                .parameters
+                .iter()
+                .map(|parameter| {
+                    parameter
+                        .defined
+                        .iter()
+                        .map(|(def, _)| def.as_str())
+                        .collect::<Vec<_>>()
+                        .join("|")
+                })
+                .collect(),
+        );
+    }
+
+    /// Builder-style variant of [`ParameterLabels::insert`].
+    pub fn with<E: EntryPoint>(mut self) -> Self {
+        self.insert::<E>();
+        self
+    }
+
+    /// Returns the label for parameter `index` of `shader`, falling back to its position.
+    pub fn label(&self, shader: &str, index: usize) -> String {
+        self.labels
+            .get(shader)
+            .and_then(|labels| labels.get(index))
+            .filter(|label| !label.is_empty())
+            .cloned()
+            .unwrap_or_else(|| format!("#{index:}"))
+    }
+}
+
+/// A permutation whose parameters are unchanged, but whose constants or types differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermutationChange {
+    pub from: Permutation,
+    pub to: Permutation,
+}
+
+/// Differences between the permutations of a single shader.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ShaderDiff {
+    pub added: Vec<Permutation>,
+    pub removed: Vec<Permutation>,
+    pub changed: Vec<PermutationChange>,
+}
+
+impl ShaderDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Differences between two sets of entry points, keyed by shader name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EntryPointDiff {
+    pub shaders: BTreeMap<String, ShaderDiff>,
+}
+
+impl EntryPointDiff {
+    /// Compares `from` against `to`.
+    ///
+    /// A removed and an added permutation that share the same parameters
+    /// are reported as a single change.
+    pub fn new(from: &EntryPoints, to: &EntryPoints) -> Self {
+        let mut from = from.clone();
+        from.normalize();
+        let mut to = to.clone();
+        to.normalize();
+
+        let mut removed = from.difference(&to);
+        let mut added = to.difference(&from);
+
+        let mut shaders = BTreeMap::<String, ShaderDiff>::new();
+
+        for (shader, removed) in std::mem::take(&mut removed.entry_points) {
+            let mut added = added.entry_points.remove(&shader).unwrap_or_default();
+            let mut diff = ShaderDiff::default();
+
+            for permutation in removed {
+                match added
+                    .iter()
+                    .position(|candidate| candidate.parameters() == permutation.parameters())
+                {
+                    Some(index) => diff.changed.push(PermutationChange {
+                        from: permutation,
+                        to: added.remove(index),
+                    }),
+                    None => diff.removed.push(permutation),
+                }
+            }
+
+            diff.added = added;
+            shaders.insert(shader, diff);
+        }
+
+        for (shader, added) in added.entry_points {
+            shaders.insert(
+                shader,
+                ShaderDiff {
+                    added,
+                    ..Default::default()
+                },
+            );
+        }
+
+        shaders.retain(|_, diff| !diff.is_empty());
+
+        EntryPointDiff { shaders }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shaders.is_empty()
+    }
+
+    /// Renders a line-based report of this diff,
+    /// prefixing added, removed and changed permutations with `+`, `-` and `~` respectively.
+    pub fn describe(&self, labels: &ParameterLabels) -> String {
+        let mut out = String::new();
+
+        for (shader, diff) in self.shaders.iter() {
+            out += shader;
+            out += "\n";
+
+            for permutation in diff.removed.iter() {
+                out += &format!(
+                    "  - {:}\n",
+                    describe_permutation(shader, permutation, labels)
+                );
+            }
+
+            for permutation in diff.added.iter() {
+                out += &format!(
+                    "  + {:}\n",
+                    describe_permutation(shader, permutation, labels)
+                );
+            }
+
+            for PermutationChange { from, to } in diff.changed.iter() {
+                out += &format!(
+                    "  ~ {:}\n      {:} -> {:}\n",
+                    describe_parameters(shader, from, labels),
+                    describe_specialization(from),
+                    describe_specialization(to),
+                );
+            }
+        }
+
+        out
+    }
+}
+
+fn describe_permutation(
+    shader: &str,
+    permutation: &Permutation,
+    labels: &ParameterLabels,
+) -> String {
+    let parameters = describe_parameters(shader, permutation, labels);
+    let specialization = describe_specialization(permutation);
+    if specialization.is_empty() {
+        parameters
+    } else {
+        parameters + " " + &specialization
+    }
+}
+
+fn describe_parameters(
+    shader: &str,
+    permutation: &Permutation,
+    labels: &ParameterLabels,
+) -> String {
+    let parameters = permutation
+        .parameters()
+        .iter()
+        .enumerate()
+        .map(|(index, value)| format!("{:}={value:}", labels.label(shader, index)))
+        .collect::<Vec<_>>();
+
+    format!("[{:}]", parameters.join(", "))
+}
+
+/// Renders the constants and types of a permutation as `{KEY=value, ..} <Key=Type, ..>`.
+fn describe_specialization(permutation: &Permutation) -> String {
+    let join = |pairs: &mut dyn Iterator<Item = (&String, &String)>| {
+        pairs
+            .map(|(key, value)| format!("{key:}={value:}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let constants = permutation.constants();
+    let constants = join(&mut constants.iter());
+    let types = join(&mut permutation.types().iter());
+
+    [
+        (!constants.is_empty()).then(|| format!("{{{constants}}}")),
+        (!types.is_empty()).then(|| format!("<{types}>")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::ShaderDefVal;
+
+    use crate::prelude::{EntryPoint, EntryPointParameters, EntryPoints, Export};
+
+    use super::{EntryPointDiff, ParameterLabels};
+
+    fn entry_points(permutations: &[(&str, &[&str], u32)]) -> EntryPoints {
+        let mut entry_points = EntryPoints::default();
+        for (shader, parameters, max_lights) in permutations {
+            entry_points.insert_export(Export {
+                shader: shader.to_string(),
+                permutation: parameters.iter().map(ToString::to_string).collect(),
+                constants: vec![ShaderDefVal::UInt("MAX_LIGHTS".into(), *max_lights)],
+                ..Default::default()
+            });
+        }
+        entry_points
+    }
+
+    enum Fragment {}
+
+    impl EntryPoint for Fragment {
+        const NAME: &'static str = "fragment";
+
+        fn parameters() -> EntryPointParameters {
+            &[(&[("VERTEX_UVS", "some")], "none"), (&[], "none")]
+        }
+    }
+
+    #[test]
+    fn classifies_added_removed_and_changed() {
+        let from = entry_points(&[
+            ("fragment", &["some", "none"], 4),
+            ("fragment", &["none", "none"], 4),
+            ("vertex", &["none"], 4),
+            ("unchanged", &["none"], 4),
+        ]);
+        let to = entry_points(&[
+            ("fragment", &["some", "none"], 8),
+            ("fragment", &["some", "some"], 4),
+            ("unchanged", &["none"], 4),
+            ("new", &["none"], 4),
+        ]);
+
+        let diff = EntryPointDiff::new(&from, &to);
+
+        assert_eq!(
+            diff.shaders.keys().collect::<Vec<_>>(),
+            vec!["fragment", "new", "vertex"]
+        );
+
+        let fragment = &diff.shaders["fragment"];
+        assert_eq!(fragment.changed.len(), 1);
+        assert_eq!(fragment.changed[0].from.parameters(), ["some", "none"]);
+        assert_eq!(fragment.changed[0].from.constants()["MAX_LIGHTS"], "4");
+        assert_eq!(fragment.changed[0].to.constants()["MAX_LIGHTS"], "8");
+        assert_eq!(fragment.removed.len(), 1);
+        assert_eq!(fragment.removed[0].parameters(), ["none", "none"]);
+        assert_eq!(fragment.added.len(), 1);
+        assert_eq!(fragment.added[0].parameters(), ["some", "some"]);
+
+        assert_eq!(diff.shaders["new"].added.len(), 1);
+        assert!(diff.shaders["new"].removed.is_empty());
+        assert_eq!(diff.shaders["vertex"].removed.len(), 1);
+        assert!(diff.shaders["vertex"].added.is_empty());
+
+        assert_eq!(
+            diff.describe(&ParameterLabels::default().with::<Fragment>()),
+            "fragment\n\
+             \x20 - [VERTEX_UVS=none, #1=none] {MAX_LIGHTS=4}\n\
+             \x20 + [VERTEX_UVS=some, #1=some] {MAX_LIGHTS=4}\n\
+             \x20 ~ [VERTEX_UVS=some, #1=none]\n\
+             \x20     {MAX_LIGHTS=4} -> {MAX_LIGHTS=8}\n\
+             new\n\
+             \x20 + [#0=none] {MAX_LIGHTS=4}\n\
+             vertex\n\
+             \x20 - [#0=none] {MAX_LIGHTS=4}\n"
+        );
+    }
+
+    #[test]
+    fn ignores_ordering_and_duplicates() {
+        let from = entry_points(&[("fragment", &["a"], 4), ("fragment", &["b"], 4)]);
+        let mut to = entry_points(&[("fragment", &["b"], 4), ("fragment", &["a"], 4)]);
+        to.get_mut("fragment").unwrap().reverse();
+        to.get_mut("fragment")
+            .unwrap()
+            .push(from.entry_points["fragment"][0].clone());
+
+        assert!(EntryPointDiff::new(&from, &to).is_empty());
+    }
+}
@@ -1,22 +1,39 @@
-//! Adds support for exporting the active entry point set to a `.json` file.
+//! Adds support for exporting the active entry point set to a `.json`, `.msgpack`, `.ron` or `.toml` file.
 //!
 //! This can be used in conjunction with `rust-gpu-builder` and `permutate-macro` to drive hot-recompiles.
 
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fs::File,
-    path::PathBuf,
-    sync::mpsc::{Receiver, SyncSender},
+    io::{BufWriter, ErrorKind, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{Receiver, Sender},
+        Arc, Mutex,
+    },
+    time::Duration,
 };
 
 use bevy::{
-    prelude::{default, info, CoreSet, Deref, DerefMut, IntoSystemConfig, NonSendMut, Plugin},
+    app::AppExit,
+    prelude::{
+        default, error, info, CoreSet, Deref, DerefMut, EventReader, EventWriter, Handle,
+        IntoSystemConfig, NonSend, NonSendMut, Plugin,
+    },
     render::render_resource::ShaderDefVal,
     tasks::IoTaskPool,
-    utils::HashMap,
+    utils::{HashMap, Instant},
 };
+use rust_gpu_builder_shared::RustGpuBuilderOutput;
 use serde::{Deserialize, Serialize};
 
+use crate::prelude::{
+    mangle_entry_point_name, metadata_types, resolve_entry_point_artifact, DynamicEntryPoint,
+    EntryPoint, EntryPointContext, RUST_GPU_LIBRARY,
+};
+
 #[cfg(feature = "hot-rebuild")]
 pub(crate) static EXPORT_HANDLES: once_cell::sync::Lazy<
     std::sync::RwLock<HashMap<PathBuf, ExportHandle>>,
@@ -27,44 +44,535 @@ pub(crate) static MATERIAL_EXPORTS: once_cell::sync::Lazy<
     std::sync::RwLock<HashMap<std::any::TypeId, PathBuf>>,
 > = once_cell::sync::Lazy::new(default);
 
-/// Export writer function wrapping `std::fs::File` and `serde_json::to_writer_pretty`
-pub fn file_writer(path: PathBuf, entry_points: EntryPoints) {
-    let writer = File::create(path).unwrap();
-    serde_json::to_writer_pretty(writer, &entry_points).unwrap();
+/// Export destinations for individual entry points, keyed by name.
+#[cfg(feature = "hot-rebuild")]
+pub(crate) static ENTRY_POINT_EXPORTS: once_cell::sync::Lazy<
+    std::sync::RwLock<HashMap<String, PathBuf>>,
+> = once_cell::sync::Lazy::new(default);
+
+/// Export destinations for individual artifacts.
+#[cfg(feature = "hot-rebuild")]
+pub(crate) static ARTIFACT_EXPORTS: once_cell::sync::Lazy<
+    std::sync::RwLock<HashMap<Handle<RustGpuBuilderOutput>, PathBuf>>,
+> = once_cell::sync::Lazy::new(default);
+
+/// Source of the permutation lengths produced by an exported entry point.
+type EntryPointArity = Box<dyn Fn() -> RangeInclusive<usize> + Send + Sync>;
+
+/// Permutation lengths produced by each exported entry point, keyed by name.
+///
+/// Evaluated when pruning, so entry points whose tables are registered at runtime report their current arity.
+#[cfg(feature = "hot-rebuild")]
+pub(crate) static ENTRY_POINT_ARITIES: once_cell::sync::Lazy<
+    std::sync::RwLock<HashMap<String, EntryPointArity>>,
+> = once_cell::sync::Lazy::new(default);
+
+/// Registers `E`'s [`EntryPoint::arity`] for use by [`ExportPruning::parameter_mismatch`].
+pub(crate) fn register_entry_point_arity<E: EntryPoint>() {
+    ENTRY_POINT_ARITIES
+        .write()
+        .unwrap()
+        .insert(E::NAME.to_string(), Box::new(E::arity));
+}
+
+/// Registers a [`DynamicEntryPoint`]'s current [`DynamicEntryPoint::arity`] for use by [`ExportPruning::parameter_mismatch`].
+pub(crate) fn register_dynamic_entry_point_arity(entry_point: &DynamicEntryPoint) {
+    let arity = entry_point.arity();
+    ENTRY_POINT_ARITIES
+        .write()
+        .unwrap()
+        .insert(entry_point.name.clone(), Box::new(move || arity.clone()));
+}
+
+/// Exports permutations of entry point `E` to `path`,
+/// regardless of the material that specialized them.
+///
+/// Takes priority over [`RustGpu::export_to`](crate::prelude::RustGpu::export_to).
+pub fn export_entry_point_to<E: EntryPoint, P: Into<PathBuf>>(path: P) {
+    ENTRY_POINT_EXPORTS
+        .write()
+        .unwrap()
+        .insert(E::NAME.to_string(), path.into());
+
+    register_entry_point_arity::<E>();
+}
+
+/// Exports permutations of a [`DynamicEntryPoint`] to `path`,
+/// regardless of the material that specialized them.
+///
+/// Takes priority over [`RustGpu::export_to`](crate::prelude::RustGpu::export_to).
+pub fn export_dynamic_entry_point_to<P: Into<PathBuf>>(entry_point: &DynamicEntryPoint, path: P) {
+    ENTRY_POINT_EXPORTS
+        .write()
+        .unwrap()
+        .insert(entry_point.name.clone(), path.into());
+
+    register_dynamic_entry_point_arity(entry_point);
+}
+
+/// Exports permutations of any entry point specialized against `artifact` to `path`,
+/// regardless of the material or entry point type that specialized them.
+///
+/// Takes priority over [`export_entry_point_to`] and [`RustGpu::export_to`](crate::prelude::RustGpu::export_to).
+pub fn export_artifact_to<P: Into<PathBuf>>(artifact: Handle<RustGpuBuilderOutput>, path: P) {
+    ARTIFACT_EXPORTS
+        .write()
+        .unwrap()
+        .insert(artifact, path.into());
+}
+
+/// Returns the export destination for the entry point named `shader`,
+/// built as `entry_point` by the material with [`TypeId`](std::any::TypeId) `material`
+/// and specialized against `artifact`.
+///
+/// Artifact destinations take priority over entry point destinations,
+/// which take priority over material destinations.
+/// Artifact destinations are looked up across `artifact` and its [`RustGpuLibrary`](crate::prelude::RustGpuLibrary) layers,
+/// preferring the artifact that currently provides `entry_point`,
+/// then the highest-priority artifact with a destination.
+pub fn export_route(
+    shader: &str,
+    entry_point: &str,
+    material: std::any::TypeId,
+    artifact: &Handle<RustGpuBuilderOutput>,
+) -> Option<PathBuf> {
+    let provider = resolve_entry_point_artifact(artifact, entry_point);
+
+    {
+        let artifact_exports = ARTIFACT_EXPORTS.read().unwrap();
+        let library = RUST_GPU_LIBRARY.read().unwrap();
+
+        let path = provider
+            .and_then(|provider| artifact_exports.get(&provider))
+            .or_else(|| {
+                library
+                    .resolution_order(artifact)
+                    .find_map(|candidate| artifact_exports.get(candidate))
+            });
+
+        if let Some(path) = path {
+            return Some(path.clone());
+        }
+    }
+
+    if let Some(path) = ENTRY_POINT_EXPORTS.read().unwrap().get(shader) {
+        return Some(path.clone());
+    }
+
+    MATERIAL_EXPORTS.read().unwrap().get(&material).cloned()
+}
+
+/// Returns every registered export destination.
+fn export_paths() -> BTreeSet<PathBuf> {
+    MATERIAL_EXPORTS
+        .read()
+        .unwrap()
+        .values()
+        .chain(ENTRY_POINT_EXPORTS.read().unwrap().values())
+        .chain(ARTIFACT_EXPORTS.read().unwrap().values())
+        .cloned()
+        .collect()
+}
+
+/// Error produced when reading or writing an entry point export.
+#[derive(Debug)]
+pub enum ExportError {
+    /// The export file could not be read or written.
+    Io(std::io::Error),
+    /// The entry points could not be serialized.
+    Serialize(String),
+    /// The export file could not be deserialized.
+    Deserialize(String),
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Io(e) => write!(f, "IO error: {e:}"),
+            ExportError::Serialize(e) => write!(f, "Serialization error: {e:}"),
+            ExportError::Deserialize(e) => write!(f, "Deserialization error: {e:}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<std::io::Error> for ExportError {
+    fn from(value: std::io::Error) -> Self {
+        ExportError::Io(value)
+    }
+}
+
+/// Event sent when an entry point export fails to write.
+#[derive(Debug)]
+pub struct ExportFailed {
+    pub path: PathBuf,
+    pub error: ExportError,
+}
+
+/// Function used to read an existing export, returning `None` if it does not exist.
+pub type ExportReader = fn(PathBuf) -> Result<Option<EntryPoints>, ExportError>;
+
+/// Serialization format of an entry point export, chosen by file extension.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ExportFormat {
+    Json,
+    #[cfg(feature = "msgpack")]
+    MsgPack,
+    #[cfg(feature = "ron")]
+    Ron,
+    #[cfg(feature = "toml")]
+    Toml,
+}
+
+impl ExportFormat {
+    /// Returns the format corresponding to the extension of `path`,
+    /// or `None` if it is unsupported or its feature is disabled.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(ExportFormat::Json),
+            #[cfg(feature = "msgpack")]
+            "msgpack" => Some(ExportFormat::MsgPack),
+            #[cfg(feature = "ron")]
+            "ron" => Some(ExportFormat::Ron),
+            #[cfg(feature = "toml")]
+            "toml" => Some(ExportFormat::Toml),
+            _ => None,
+        }
+    }
+
+    /// Serializes a set of entry points into this format.
+    pub fn serialize(&self, entry_points: &EntryPoints) -> Result<Vec<u8>, ExportError> {
+        let error = |e: &dyn std::fmt::Display| ExportError::Serialize(e.to_string());
+        match self {
+            ExportFormat::Json => serde_json::to_vec_pretty(entry_points).map_err(|e| error(&e)),
+            #[cfg(feature = "msgpack")]
+            ExportFormat::MsgPack => rmp_serde::to_vec_named(entry_points).map_err(|e| error(&e)),
+            #[cfg(feature = "ron")]
+            ExportFormat::Ron => ron::ser::to_string_pretty(entry_points, default())
+                .map(String::into_bytes)
+                .map_err(|e| error(&e)),
+            #[cfg(feature = "toml")]
+            ExportFormat::Toml => toml::to_string_pretty(entry_points)
+                .map(String::into_bytes)
+                .map_err(|e| error(&e)),
+        }
+    }
+
+    /// Deserializes a set of entry points from this format.
+    pub fn deserialize(&self, bytes: &[u8]) -> Result<EntryPoints, ExportError> {
+        let error = |e: &dyn std::fmt::Display| ExportError::Deserialize(e.to_string());
+        match self {
+            ExportFormat::Json => serde_json::from_slice(bytes).map_err(|e| error(&e)),
+            #[cfg(feature = "msgpack")]
+            ExportFormat::MsgPack => rmp_serde::from_slice(bytes).map_err(|e| error(&e)),
+            #[cfg(feature = "ron")]
+            ExportFormat::Ron => ron::de::from_bytes(bytes).map_err(|e| error(&e)),
+            #[cfg(feature = "toml")]
+            ExportFormat::Toml => std::str::from_utf8(bytes)
+                .map_err(|e| error(&e))
+                .and_then(|source| toml::from_str(source).map_err(|e| error(&e))),
+        }
+    }
+}
+
+/// Returns the [`ExportFormat`] for `path`, or an error if its extension is unsupported.
+fn export_format(path: &Path) -> Result<ExportFormat, ExportError> {
+    ExportFormat::from_path(path).ok_or_else(|| {
+        ExportError::Io(std::io::Error::new(
+            ErrorKind::Unsupported,
+            format!("Unsupported export format for {:}", path.display()),
+        ))
+    })
+}
+
+/// Per-path locks serializing [`file_writer`] calls,
+/// as debounced writes and the exit flush may run concurrently on the `IoTaskPool`.
+static FILE_WRITER_LOCKS: once_cell::sync::Lazy<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> =
+    once_cell::sync::Lazy::new(default);
+
+/// Counter used to give each [`file_writer`] temporary file a unique name.
+static FILE_WRITER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Export reader function wrapping `std::fs::read`,
+/// deserializing with the [`ExportFormat`] matching the extension of `path`.
+pub fn file_reader(path: PathBuf) -> Result<Option<EntryPoints>, ExportError> {
+    let format = export_format(&path)?;
+
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    format.deserialize(&bytes).map(Some)
+}
+
+/// Export writer function wrapping `std::fs::File`,
+/// serializing with the [`ExportFormat`] matching the extension of `path`.
+///
+/// Writes to a uniquely-named temporary file before renaming it over `path`,
+/// so readers never observe a partially-written export.
+/// Concurrent writes to the same path are serialized.
+///
+/// Skips the write if `path` already contains identical output.
+pub fn file_writer(path: PathBuf, mut entry_points: EntryPoints) -> Result<(), ExportError> {
+    let format = export_format(&path)?;
+
+    entry_points.normalize();
+
+    let bytes = format.serialize(&entry_points)?;
+
+    let lock = FILE_WRITER_LOCKS
+        .lock()
+        .unwrap()
+        .entry(path.clone())
+        .or_default()
+        .clone();
+    let _guard = lock.lock().unwrap();
+
+    if std::fs::read(&path).ok().as_deref() == Some(bytes.as_slice()) {
+        return Ok(());
+    }
+
+    let mut tmp_path = path.clone().into_os_string();
+    tmp_path.push(format!(
+        ".{:}.{:}.tmp",
+        std::process::id(),
+        FILE_WRITER_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let result = (|| {
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        writer.write_all(&bytes)?;
+        writer.flush()?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        std::fs::remove_file(&tmp_path).ok();
+    }
+
+    result
+}
+
+/// Policy for combining an existing export with the permutations discovered by the current session.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ExportMergePolicy {
+    /// Merge newly-discovered permutations into those already present in the export.
+    #[default]
+    Accumulate,
+    /// Discard the contents of the existing export.
+    Replace,
+    /// Merge newly-discovered permutations into those already present in the export,
+    /// dropping any existing permutations that are not rediscovered within the given duration.
+    AccumulateWithExpiry(Duration),
+}
+
+/// Criteria for dropping stale permutations when an existing export is loaded.
+///
+/// Session and age based pruning rely on [`PermutationUsage`] statistics, and enable their collection.
+/// Permutations without statistics are only pruned on parameter mismatch.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExportPruning {
+    /// Drop permutations that have gone unseen for more than this many sessions.
+    pub max_sessions_unseen: Option<u32>,
+    /// Drop permutations that were last seen longer ago than this.
+    pub max_age: Option<Duration>,
+    /// Drop permutations whose parameter count is outside their entry point's current [`EntryPoint::arity`].
+    pub parameter_mismatch: bool,
+}
+
+impl ExportPruning {
+    /// Returns true if any pruning criteria are enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.requires_usage_statistics() || self.parameter_mismatch
+    }
+
+    /// Returns true if any enabled criteria depend on [`PermutationUsage`] statistics.
+    pub fn requires_usage_statistics(&self) -> bool {
+        self.max_sessions_unseen.is_some() || self.max_age.is_some()
+    }
+
+    /// Returns true if `permutation` of entry point `shader` should be pruned.
+    ///
+    /// `now` is the current time in seconds since the UNIX epoch,
+    /// and `arities` the current [`EntryPoint::arity`] of each known entry point.
+    pub fn is_stale(
+        &self,
+        shader: &str,
+        permutation: &Permutation,
+        now: u64,
+        arities: &HashMap<String, RangeInclusive<usize>>,
+    ) -> bool {
+        if self.parameter_mismatch {
+            if let Some(arity) = arities.get(shader) {
+                if !arity.contains(&permutation.parameters.len()) {
+                    return true;
+                }
+            }
+        }
+
+        let Some(usage) = &permutation.usage else {
+            return false;
+        };
+
+        if let Some(max_sessions_unseen) = self.max_sessions_unseen {
+            if usage.sessions_unseen > max_sessions_unseen {
+                return true;
+            }
+        }
+
+        if let Some(max_age) = self.max_age {
+            if now.saturating_sub(usage.last_seen) > max_age.as_secs() {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+/// Controls how bursts of changes to an export are batched into a single write.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExportDebounce {
+    /// Time to wait after the most recent change before writing.
+    pub window: Duration,
+    /// Maximum time a change may go unwritten while further changes keep arriving.
+    pub max_latency: Duration,
+}
+
+impl Default for ExportDebounce {
+    fn default() -> Self {
+        ExportDebounce {
+            window: Duration::from_millis(250),
+            max_latency: Duration::from_secs(2),
+        }
+    }
 }
 
 /// Handles exporting known `RustGpuMaterial` permutations to a JSON file for static compilation.
 pub struct EntryPointExportPlugin<F> {
     pub writer: F,
+    pub reader: ExportReader,
+    pub merge_policy: ExportMergePolicy,
+    pub debounce: ExportDebounce,
+    /// Whether to record [`PermutationUsage`] statistics for each exported permutation.
+    pub usage_statistics: bool,
+    pub pruning: ExportPruning,
 }
 
-impl Default for EntryPointExportPlugin<fn(PathBuf, EntryPoints)> {
+impl Default for EntryPointExportPlugin<fn(PathBuf, EntryPoints) -> Result<(), ExportError>> {
     fn default() -> Self {
         EntryPointExportPlugin {
             writer: file_writer,
+            reader: file_reader,
+            merge_policy: default(),
+            debounce: default(),
+            usage_statistics: false,
+            pruning: default(),
+        }
+    }
+}
+
+impl<F> EntryPointExportPlugin<F>
+where
+    F: Fn(PathBuf, EntryPoints) -> Result<(), ExportError>,
+{
+    /// Writes `exports` to `path` in a single pass without running an app,
+    /// applying this plugin's reader, merge policy, pruning and writer as a running app would.
+    ///
+    /// As inherited permutations have no later opportunity to be rediscovered,
+    /// [`ExportMergePolicy::AccumulateWithExpiry`] drops any that are not among `exports`.
+    pub fn export<P: Into<PathBuf>>(
+        &self,
+        path: P,
+        exports: impl IntoIterator<Item = Export>,
+    ) -> Result<(), ExportError> {
+        let path = path.into();
+
+        let (mut entry_points, _) =
+            load_existing_export(&path, self.reader, self.merge_policy, self.pruning)?;
+
+        let mut inherited = match self.merge_policy {
+            ExportMergePolicy::AccumulateWithExpiry(_) => Some(entry_points.clone()),
+            _ => None,
+        };
+
+        let usage_statistics = self.usage_statistics || self.pruning.requires_usage_statistics();
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        for export in exports {
+            let shader = export.shader.clone();
+            let material = export.material;
+            let permutation = Permutation::from(export);
+
+            if let Some(inherited) = &mut inherited {
+                inherited.remove_permutation(&shader, &permutation);
+            }
+
+            entry_points.insert_permutation(&shader, permutation.clone());
+
+            if usage_statistics {
+                if let Some(permutation) = entry_points.permutation_mut(&shader, &permutation) {
+                    permutation
+                        .usage
+                        .get_or_insert_with(default)
+                        .record(material, time);
+                }
+            }
         }
+
+        for (shader, permutations) in inherited.unwrap_or_default().entry_points {
+            for permutation in permutations {
+                entry_points.remove_permutation(&shader, &permutation);
+            }
+        }
+
+        (self.writer)(path, entry_points)
     }
 }
 
 impl<F> Plugin for EntryPointExportPlugin<F>
 where
-    F: Fn(PathBuf, EntryPoints) + Clone + Send + Sync + 'static,
+    F: Fn(PathBuf, EntryPoints) -> Result<(), ExportError> + Clone + Send + Sync + 'static,
 {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.world.init_non_send_resource::<EntryPointExport>();
+        app.world.insert_non_send_resource(EntryPointExport {
+            usage_statistics: self.usage_statistics || self.pruning.requires_usage_statistics(),
+            ..default()
+        });
+
+        app.add_event::<ExportFailed>();
 
         app.add_systems((
-            EntryPointExport::create_export_containers_system.in_base_set(CoreSet::Update),
+            EntryPointExport::create_export_containers_system(
+                self.reader,
+                self.merge_policy,
+                self.pruning,
+            )
+            .in_base_set(CoreSet::Update),
             EntryPointExport::receive_entry_points_system.in_base_set(CoreSet::Last),
-            EntryPointExport::export_entry_points_system(self.writer.clone())
+            EntryPointExport::expire_entry_points_system
                 .in_base_set(CoreSet::Last)
                 .after(EntryPointExport::receive_entry_points_system),
+            EntryPointExport::export_entry_points_system(self.writer.clone(), self.debounce)
+                .in_base_set(CoreSet::Last)
+                .after(EntryPointExport::expire_entry_points_system),
+            EntryPointExport::export_results_system.in_base_set(CoreSet::First),
         ));
     }
 }
 
-/// Handle to an entry point file export
-pub type ExportHandle = SyncSender<Export>;
+/// Handle to an entry point file export.
+///
+/// Unbounded, so that sending from `Material::specialize` never blocks the render thread.
+pub type ExportHandle = Sender<Export>;
 
 /// MPSC reciever carrying entry points for export.
 type EntryPointReceiver = Receiver<Export>;
@@ -72,10 +580,56 @@ type EntryPointReceiver = Receiver<Export>;
 /// MPSC message describing an entry point.
 #[derive(Debug, Default, Clone)]
 pub struct Export {
-    pub shader: &'static str,
+    pub shader: String,
+    /// Type name of the material that requested this entry point.
+    pub material: &'static str,
     pub permutation: Vec<String>,
     pub constants: Vec<ShaderDefVal>,
-    pub types: BTreeMap<String, String>,
+    /// Generic type instantiation, in declaration order.
+    pub types: Vec<(String, String)>,
+}
+
+impl Export {
+    /// Describes the permutation of entry point `E` requested by `material` with the provided specialization inputs.
+    pub fn entry_point<E: EntryPoint>(material: &'static str, context: &EntryPointContext) -> Self {
+        Export {
+            shader: E::NAME.to_string(),
+            material,
+            permutation: E::permutation_with(context),
+            constants: E::filter_constants(context.shader_defs),
+            types: E::types_with(context),
+        }
+    }
+
+    /// Describes the permutation of a [`DynamicEntryPoint`] requested by `material` with the provided shader defs.
+    pub fn dynamic_entry_point(
+        entry_point: &DynamicEntryPoint,
+        material: &'static str,
+        shader_defs: &[ShaderDefVal],
+    ) -> Self {
+        Export {
+            shader: entry_point.name.clone(),
+            material,
+            permutation: entry_point.permutation(shader_defs),
+            constants: entry_point.filter_constants(shader_defs),
+            types: entry_point.types.clone(),
+        }
+    }
+
+    /// Describes the permutation of a [`DynamicEntryPoint`] requested by `material` during specialization.
+    pub fn dynamic_entry_point_with(
+        entry_point: &DynamicEntryPoint,
+        material: &'static str,
+        context: &EntryPointContext,
+    ) -> Self {
+        Export {
+            shader: entry_point.name.clone(),
+            material,
+            permutation: entry_point.permutation_with(context),
+            constants: entry_point.filter_constants(context.shader_defs),
+            types: entry_point.types_with(context),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -86,20 +640,27 @@ enum PermutationConstant {
     Int(i32),
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, Deref, DerefMut)]
+#[derive(
+    Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Deref, DerefMut,
+)]
+#[serde(transparent)]
 struct PermutationConstants {
-    #[serde(flatten)]
-    constants: HashMap<String, PermutationConstant>,
+    constants: BTreeMap<String, PermutationConstant>,
 }
 
 impl From<Vec<ShaderDefVal>> for PermutationConstants {
+    /// Non-negative integers are stored as [`PermutationConstant::Uint`],
+    /// as they are indistinguishable once serialized.
     fn from(value: Vec<ShaderDefVal>) -> Self {
         PermutationConstants {
             constants: value
                 .into_iter()
                 .map(|def| match def {
                     ShaderDefVal::Bool(key, value) => (key, PermutationConstant::Bool(value)),
-                    ShaderDefVal::Int(key, value) => (key, PermutationConstant::Int(value)),
+                    ShaderDefVal::Int(key, value) => match u32::try_from(value) {
+                        Ok(value) => (key, PermutationConstant::Uint(value)),
+                        Err(_) => (key, PermutationConstant::Int(value)),
+                    },
                     ShaderDefVal::UInt(key, value) => (key, PermutationConstant::Uint(value)),
                 })
                 .collect(),
@@ -107,18 +668,301 @@ impl From<Vec<ShaderDefVal>> for PermutationConstants {
     }
 }
 
+/// Reads permutation types from their map form,
+/// or the array of pairs briefly written in its place.
+fn deserialize_types<'de, D>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Map(BTreeMap<String, String>),
+        Pairs(Vec<(String, String)>),
+    }
+
+    Ok(match Repr::deserialize(deserializer)? {
+        Repr::Map(types) => types,
+        Repr::Pairs(types) => types.into_iter().collect(),
+    })
+}
+
+/// Optional statistics describing how a permutation has been used.
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermutationUsage {
+    /// Seconds since the UNIX epoch at which the permutation was first specialized.
+    pub first_seen: u64,
+    /// Seconds since the UNIX epoch at which the permutation was last specialized.
+    pub last_seen: u64,
+    /// Number of times the permutation has been specialized.
+    pub count: u64,
+    /// Type names of the materials that requested the permutation.
+    pub materials: BTreeSet<String>,
+    /// Number of consecutive sessions in which the permutation was not specialized.
+    #[serde(default)]
+    pub sessions_unseen: u32,
+}
+
+impl PermutationUsage {
+    /// Records a specialization of the permutation by `material` at `time`.
+    pub fn record(&mut self, material: &str, time: u64) {
+        if self.count == 0 {
+            self.first_seen = time;
+        }
+        self.first_seen = self.first_seen.min(time);
+        self.last_seen = self.last_seen.max(time);
+        self.count += 1;
+        self.materials.insert(material.to_string());
+        self.sessions_unseen = 0;
+    }
+
+    /// Combines the statistics of `other` into `self`.
+    pub fn merge(&mut self, other: PermutationUsage) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = other;
+            return;
+        }
+        self.first_seen = self.first_seen.min(other.first_seen);
+        self.last_seen = self.last_seen.max(other.last_seen);
+        self.count += other.count;
+        self.materials.extend(other.materials);
+        self.sessions_unseen = self.sessions_unseen.min(other.sessions_unseen);
+    }
+}
+
+/// Serializable container for a single permutation of an entry point.
+///
+/// Equality and ordering only consider the permutation itself, not its [`PermutationUsage`].
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Permutation {
     parameters: Vec<String>,
     constants: PermutationConstants,
+    #[serde(default, deserialize_with = "deserialize_types")]
     types: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<PermutationUsage>,
+}
+
+impl Permutation {
+    /// Returns the positional parameter values of this permutation.
+    pub fn parameters(&self) -> &[String] {
+        &self.parameters
+    }
+
+    /// Returns the constants of this permutation, rendered as strings.
+    pub fn constants(&self) -> BTreeMap<String, String> {
+        self.constants
+            .iter()
+            .map(|(key, constant)| {
+                let value = match constant {
+                    PermutationConstant::Bool(value) => value.to_string(),
+                    PermutationConstant::Uint(value) => value.to_string(),
+                    PermutationConstant::Int(value) => value.to_string(),
+                };
+                (key.clone(), value)
+            })
+            .collect()
+    }
+
+    /// Returns the types of this permutation.
+    pub fn types(&self) -> &BTreeMap<String, String> {
+        &self.types
+    }
+
+    /// Returns the mangled name of this permutation of entry point `shader`, as per [`EntryPoint::build`].
+    ///
+    /// Types are mangled in the declaration order of any registered [`RustGpuEntryPointMetadata`](crate::prelude::RustGpuEntryPointMetadata) for `shader`,
+    /// falling back to key order.
+    pub fn entry_point_name(&self, shader: &str) -> String {
+        let declared = metadata_types(shader);
+        self.entry_point_name_ordered(shader, declared.iter().map(|(key, _)| key.as_str()))
+    }
+
+    /// Returns the mangled name of this permutation of entry point `E`, as per [`EntryPoint::build`].
+    ///
+    /// Types are mangled in the declaration order of [`EntryPoint::types`].
+    pub fn entry_point_name_for<E: EntryPoint>(&self) -> String {
+        let declared = E::types();
+        self.entry_point_name_ordered(E::NAME, declared.iter().map(|(key, _)| key.as_str()))
+    }
+
+    /// Returns the mangled name of this permutation of entry point `shader`,
+    /// with types ordered as per `declared`, followed by any undeclared types in key order.
+    pub fn entry_point_name_ordered<'a>(
+        &self,
+        shader: &str,
+        declared: impl IntoIterator<Item = &'a str>,
+    ) -> String {
+        let mut types = self.types.clone();
+        let types = declared
+            .into_iter()
+            .filter_map(|key| types.remove_entry(key))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .chain(types);
+
+        mangle_entry_point_name(
+            shader,
+            self.parameters.iter().cloned(),
+            self.constants.iter().map(|(key, constant)| {
+                let value = match constant {
+                    // `EntryPoint::build` renders boolean constants by name
+                    PermutationConstant::Bool(_) => key.clone(),
+                    PermutationConstant::Uint(value) => value.to_string(),
+                    PermutationConstant::Int(value) => value.to_string(),
+                };
+                (key.clone(), value)
+            }),
+            types,
+        )
+    }
+
+    fn key(
+        &self,
+    ) -> (
+        &Vec<String>,
+        &PermutationConstants,
+        &BTreeMap<String, String>,
+    ) {
+        (&self.parameters, &self.constants, &self.types)
+    }
+}
+
+impl PartialEq for Permutation {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Permutation {}
+
+impl PartialOrd for Permutation {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Permutation {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl From<Export> for Permutation {
+    fn from(export: Export) -> Self {
+        Permutation {
+            parameters: export.permutation,
+            constants: export.constants.into(),
+            types: export.types.into_iter().collect(),
+            usage: None,
+        }
+    }
 }
 
 /// Serializable container for a single entry point
-#[derive(Debug, Default, Clone, Deref, DerefMut, Serialize, Deserialize)]
+///
+/// Entry points are ordered by name, and their permutations kept sorted,
+/// so that serialized output is stable across runs.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deref, DerefMut, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct EntryPoints {
-    #[serde(flatten)]
-    pub entry_points: HashMap<String, Vec<Permutation>>,
+    pub entry_points: BTreeMap<String, Vec<Permutation>>,
+}
+
+impl EntryPoints {
+    /// Removes a permutation from the given entry point, removing the entry point if it becomes empty.
+    ///
+    /// Returns false if the permutation was not present.
+    pub fn remove_permutation(&mut self, shader: &str, permutation: &Permutation) -> bool {
+        let Some(permutations) = self.entry_points.get_mut(shader) else {
+            return false;
+        };
+
+        let Ok(index) = permutations.binary_search(permutation) else {
+            return false;
+        };
+
+        permutations.remove(index);
+        if permutations.is_empty() {
+            self.entry_points.remove(shader);
+        }
+
+        true
+    }
+
+    /// Inserts a permutation for the given entry point at its sorted position.
+    ///
+    /// Returns false if the permutation was already present.
+    pub fn insert_permutation(&mut self, shader: &str, permutation: Permutation) -> bool {
+        let permutations = self.entry_points.entry(shader.to_string()).or_default();
+        match permutations.binary_search(&permutation) {
+            Ok(_) => false,
+            Err(index) => {
+                permutations.insert(index, permutation);
+                true
+            }
+        }
+    }
+
+    /// Inserts the permutation described by an [`Export`] message at its sorted position.
+    ///
+    /// Returns false if the permutation was already present.
+    pub fn insert_export(&mut self, export: Export) -> bool {
+        let shader = export.shader.clone();
+        self.insert_permutation(&shader, export.into())
+    }
+
+    /// Returns a mutable reference to the given permutation of an entry point, if present.
+    pub fn permutation_mut(
+        &mut self,
+        shader: &str,
+        permutation: &Permutation,
+    ) -> Option<&mut Permutation> {
+        let permutations = self.entry_points.get_mut(shader)?;
+        let index = permutations.binary_search(permutation).ok()?;
+        permutations.get_mut(index)
+    }
+
+    /// Sorts and deduplicates each entry point's permutations,
+    /// merging the usage statistics of any duplicates.
+    pub fn normalize(&mut self) {
+        for permutations in self.entry_points.values_mut() {
+            permutations.sort();
+            permutations.dedup_by(|duplicate, retained| {
+                if duplicate != retained {
+                    return false;
+                }
+
+                if let Some(usage) = duplicate.usage.take() {
+                    retained.usage.get_or_insert_with(default).merge(usage);
+                }
+
+                true
+            });
+        }
+    }
+
+    /// Returns the permutations present in `self` but not in `other`.
+    pub fn difference(&self, other: &EntryPoints) -> EntryPoints {
+        let mut difference = EntryPoints::default();
+        for (shader, permutations) in self.entry_points.iter() {
+            for permutation in permutations {
+                let present = other
+                    .entry_points
+                    .get(shader)
+                    .map(|others| others.contains(permutation))
+                    .unwrap_or_default();
+
+                if !present {
+                    difference.insert_permutation(shader, permutation.clone());
+                }
+            }
+        }
+        difference
+    }
 }
 
 /// Container for a set of entry points, with MPSC handles and change tracking
@@ -126,86 +970,894 @@ pub struct EntryPoints {
 struct EntryPointExportContainer {
     rx: EntryPointReceiver,
     entry_points: EntryPoints,
-    changed: bool,
+    /// Times of the earliest and latest changes yet to be written, if any.
+    changed: Option<(Instant, Instant)>,
+    /// Whether usage statistics have changed since the last write.
+    ///
+    /// Usage alone does not count as a change, so is only written alongside other changes or on exit,
+    /// to avoid rewriting the export (and triggering a rebuild) every time a permutation is specialized.
+    usage_changed: bool,
+    /// Permutations loaded from the existing export that have yet to be rediscovered,
+    /// and the time at which they expire.
+    inherited: Option<(EntryPoints, Instant)>,
+    /// Generation of the most recently issued write.
+    generation: usize,
+    /// Generation of the most recently completed write, locked for the duration of each write.
+    written: Arc<Mutex<usize>>,
+}
+
+impl EntryPointExportContainer {
+    /// Records a change to be written once debouncing allows.
+    fn mark_changed(&mut self) {
+        let now = Instant::now();
+        let first = self.changed.map(|(first, _)| first).unwrap_or(now);
+        self.changed = Some((first, now));
+    }
+
+    /// Issues a new write generation, clearing any pending changes.
+    fn next_generation(&mut self) -> usize {
+        self.changed = None;
+        self.usage_changed = false;
+        self.generation += 1;
+        self.generation
+    }
+}
+
+/// Runs `write` as generation `generation`, unless a newer generation has already been written.
+///
+/// Writes are serialized via `written`, so a stale background write can neither
+/// overwrite nor interleave with a newer one, such as the flush on [`AppExit`].
+fn write_generation(
+    written: &Mutex<usize>,
+    generation: usize,
+    write: impl FnOnce() -> Result<(), ExportError>,
+) -> Option<Result<(), ExportError>> {
+    let mut written = written.lock().unwrap();
+    if *written > generation {
+        return None;
+    }
+
+    let result = write();
+    *written = generation;
+    Some(result)
 }
 
 /// Non-send resource used to register export files and aggregate their entry points.
-#[derive(Debug, Default, Deref, DerefMut)]
+#[derive(Debug)]
 struct EntryPointExport {
     exports: HashMap<PathBuf, EntryPointExportContainer>,
+    usage_statistics: bool,
+    results_tx: Sender<(PathBuf, Result<(), ExportError>)>,
+    results_rx: Receiver<(PathBuf, Result<(), ExportError>)>,
+}
+
+impl Default for EntryPointExport {
+    fn default() -> Self {
+        let (results_tx, results_rx) = std::sync::mpsc::channel();
+        EntryPointExport {
+            exports: default(),
+            usage_statistics: false,
+            results_tx,
+            results_rx,
+        }
+    }
+}
+
+/// Drops any stale permutations loaded from `path`.
+///
+/// Returns true if any permutations were dropped.
+fn prune(path: &Path, entry_points: &mut EntryPoints, pruning: ExportPruning) -> bool {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    let arities = ENTRY_POINT_ARITIES
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(name, arity)| (name.clone(), arity()))
+        .collect::<HashMap<_, _>>();
+
+    let mut changed = false;
+    for (shader, permutations) in entry_points.entry_points.iter_mut() {
+        permutations.retain_mut(|permutation| {
+            if pruning.is_stale(shader, permutation, now, &arities) {
+                info!(
+                    "Pruned stale permutation for {:}: {:?}",
+                    path.display(),
+                    permutation
+                );
+                changed = true;
+                return false;
+            }
+
+            true
+        });
+    }
+
+    entry_points
+        .entry_points
+        .retain(|_, permutations| !permutations.is_empty());
+
+    changed
+}
+
+/// Reads the existing export at `path` as per `merge_policy`, dropping stale permutations as per `pruning`.
+///
+/// Returns the normalized contents of the export, and whether any permutations were pruned.
+fn load_existing_export(
+    path: &Path,
+    reader: ExportReader,
+    merge_policy: ExportMergePolicy,
+    pruning: ExportPruning,
+) -> Result<(EntryPoints, bool), ExportError> {
+    let mut entry_points = if merge_policy == ExportMergePolicy::Replace {
+        EntryPoints::default()
+    } else {
+        reader(path.to_path_buf())?.unwrap_or_default()
+    };
+    entry_points.normalize();
+
+    let pruned = pruning.is_enabled() && prune(path, &mut entry_points, pruning);
+
+    Ok((entry_points, pruned))
+}
+
+/// Starts a new usage session, assuming every permutation goes unseen until it is specialized.
+///
+/// Returns true if any usage statistics were modified.
+fn start_session(entry_points: &mut EntryPoints) -> bool {
+    let mut changed = false;
+    for usage in entry_points
+        .entry_points
+        .values_mut()
+        .flatten()
+        .filter_map(|permutation| permutation.usage.as_mut())
+    {
+        usage.sessions_unseen += 1;
+        changed = true;
+    }
+    changed
 }
 
 impl EntryPointExport {
-    /// System used to populate export containers for registered materials
-    pub fn create_export_containers_system(mut exports: NonSendMut<Self>) {
-        let material_exports = MATERIAL_EXPORTS.read().unwrap();
-        for (_, path) in material_exports.iter() {
-            if !exports.contains_key(path) {
-                let (tx, rx) = std::sync::mpsc::sync_channel::<Export>(32);
-
-                EXPORT_HANDLES.write().unwrap().insert(path.clone(), tx);
-
-                let container = EntryPointExportContainer {
-                    rx,
-                    entry_points: default(),
-                    changed: default(),
-                };
+    /// System used to populate export containers for registered materials,
+    /// merging in the contents of any existing export as per `merge_policy`,
+    /// and dropping stale permutations as per `pruning`.
+    pub fn create_export_containers_system(
+        reader: ExportReader,
+        merge_policy: ExportMergePolicy,
+        pruning: ExportPruning,
+    ) -> impl Fn(NonSendMut<Self>) + Send + Sync + 'static {
+        move |mut exports: NonSendMut<Self>| {
+            for path in export_paths().iter() {
+                if !exports.exports.contains_key(path) {
+                    let (tx, rx) = std::sync::mpsc::channel::<Export>();
+
+                    EXPORT_HANDLES.write().unwrap().insert(path.clone(), tx);
+
+                    let (mut entry_points, pruned) =
+                        match load_existing_export(path, reader, merge_policy, pruning) {
+                            Ok(existing) => existing,
+                            Err(e) => {
+                                error!(
+                                    "Failed to read existing entry points from {:}: {e:}",
+                                    path.display()
+                                );
+                                default()
+                            }
+                        };
+
+                    let usage_changed = pruning.is_enabled() && start_session(&mut entry_points);
+
+                    let inherited = match merge_policy {
+                        ExportMergePolicy::AccumulateWithExpiry(expiry) => {
+                            Some((entry_points.clone(), Instant::now() + expiry))
+                        }
+                        _ => None,
+                    };
 
-                exports.insert(path.clone(), container);
+                    let mut container = EntryPointExportContainer {
+                        rx,
+                        entry_points,
+                        changed: None,
+                        usage_changed,
+                        inherited,
+                        generation: 0,
+                        written: default(),
+                    };
+
+                    if pruned {
+                        container.mark_changed();
+                    }
+
+                    exports.exports.insert(path.clone(), container);
+                }
             }
         }
     }
 
     /// System used to receive and store entry points sent from materials.
     pub fn receive_entry_points_system(mut exports: NonSendMut<Self>) {
+        let exports = &mut *exports;
         for (_, export) in exports.exports.iter_mut() {
             while let Ok(entry_point) = export.rx.try_recv() {
-                if !export.entry_points.contains_key(entry_point.shader) {
+                if !export.entry_points.contains_key(&entry_point.shader) {
                     info!("New entry point: {}", entry_point.shader);
                     export
                         .entry_points
-                        .insert(entry_point.shader.to_string(), default());
-                    export.changed = true;
+                        .insert(entry_point.shader.clone(), default());
+                    export.mark_changed();
                 }
 
-                let entry = &export.entry_points[entry_point.shader];
                 let permutation = Permutation {
                     parameters: entry_point.permutation,
                     constants: entry_point.constants.into(),
-                    types: entry_point.types,
+                    types: entry_point.types.into_iter().collect(),
+                    usage: None,
                 };
 
-                if !entry.contains(&permutation) {
+                if let Some((inherited, _)) = &mut export.inherited {
+                    inherited.remove_permutation(&entry_point.shader, &permutation);
+                }
+
+                if export
+                    .entry_points
+                    .insert_permutation(&entry_point.shader, permutation.clone())
+                {
                     info!("New permutation: {:?}", permutation);
+                    export.mark_changed();
+                }
+
+                if exports.usage_statistics {
+                    let time = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|duration| duration.as_secs())
+                        .unwrap_or_default();
+
+                    if let Some(permutation) = export
+                        .entry_points
+                        .permutation_mut(&entry_point.shader, &permutation)
+                    {
+                        permutation
+                            .usage
+                            .get_or_insert_with(default)
+                            .record(entry_point.material, time);
+                        export.usage_changed = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// System used to drop inherited permutations that were not rediscovered before expiring.
+    pub fn expire_entry_points_system(mut exports: NonSendMut<Self>) {
+        let now = Instant::now();
+        for (path, export) in exports.exports.iter_mut() {
+            let Some((_, expiry)) = &export.inherited else {
+                continue;
+            };
+
+            if now < *expiry {
+                continue;
+            }
+
+            let (inherited, _) = export.inherited.take().unwrap();
+            for (shader, permutations) in inherited.entry_points {
+                for permutation in permutations {
+                    info!(
+                        "Expired permutation for {:}: {:?}",
+                        path.display(),
+                        permutation
+                    );
                     export
                         .entry_points
-                        .get_mut(entry_point.shader)
-                        .unwrap()
-                        .push(permutation);
-                    export.changed = true;
+                        .remove_permutation(&shader, &permutation);
+                    export.mark_changed();
                 }
             }
         }
     }
 
     /// System used to write active entry point sets to their respective files on change via the IO task pool.
-    pub fn export_entry_points_system<F>(f: F) -> impl Fn(NonSendMut<Self>) + Send + Sync + 'static
+    ///
+    /// Writes are deferred until changes settle as per `debounce`,
+    /// and any pending changes are written synchronously on [`AppExit`],
+    /// after any outstanding background write and in place of any that have yet to start.
+    pub fn export_entry_points_system<F>(
+        f: F,
+        debounce: ExportDebounce,
+    ) -> impl Fn(NonSendMut<Self>, EventReader<AppExit>) + Send + Sync + 'static
     where
-        F: Fn(PathBuf, EntryPoints) + Clone + Send + Sync + 'static,
+        F: Fn(PathBuf, EntryPoints) -> Result<(), ExportError> + Clone + Send + Sync + 'static,
     {
-        move |mut exports: NonSendMut<Self>| {
+        move |mut exports: NonSendMut<Self>, mut app_exit: EventReader<AppExit>| {
+            let exiting = app_exit.iter().count() > 0;
+            let now = Instant::now();
+
+            let exports = &mut *exports;
             for (path, export) in exports.exports.iter_mut() {
-                if export.changed {
+                if exiting {
+                    if export.changed.is_some() || export.usage_changed {
+                        info!("Flushing entry points to {:}", path.display());
+                        let generation = export.next_generation();
+                        if let Some(Err(e)) = write_generation(&export.written, generation, || {
+                            f(path.clone(), export.entry_points.clone())
+                        }) {
+                            error!("Failed to export entry points to {:}: {e:}", path.display());
+                        }
+                    }
+                    continue;
+                }
+
+                let Some((first, last)) = export.changed else {
+                    continue;
+                };
+
+                if now - last >= debounce.window || now - first >= debounce.max_latency {
                     let entry_points = export.entry_points.clone();
                     let path = path.clone();
                     let f = f.clone();
-                    info!("Exporting entry points to {:}", path.to_str().unwrap());
+                    let results_tx = exports.results_tx.clone();
+                    let generation = export.next_generation();
+                    let written = export.written.clone();
+                    info!("Exporting entry points to {:}", path.display());
                     IoTaskPool::get()
-                        .spawn(async move { f(path, entry_points) })
+                        .spawn(async move {
+                            let Some(result) = write_generation(&written, generation, || {
+                                f(path.clone(), entry_points)
+                            }) else {
+                                info!("Skipping superseded export to {:}", path.display());
+                                return;
+                            };
+                            results_tx.send((path, result)).ok();
+                        })
                         .detach();
-                    export.changed = false;
                 }
             }
         }
     }
+
+    /// System used to report the results of completed export writes.
+    pub fn export_results_system(
+        exports: NonSend<Self>,
+        mut export_failed: EventWriter<ExportFailed>,
+    ) {
+        while let Ok((path, result)) = exports.results_rx.try_recv() {
+            if let Err(error) = result {
+                error!(
+                    "Failed to export entry points to {:}: {error:}",
+                    path.display()
+                );
+                export_failed.send(ExportFailed { path, error });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::Path,
+        sync::Mutex,
+        time::{Duration, SystemTime},
+    };
+
+    use bevy::{
+        ecs::system::System,
+        pbr::MeshPipelineKey,
+        prelude::{IntoSystem, Mesh, World},
+        render::{mesh::PrimitiveTopology, render_resource::ShaderDefVal},
+    };
+
+    use crate::prelude::{
+        DynamicEntryPoint, EntryPoint, EntryPointConstants, EntryPointContext, EntryPointMeshKey,
+        EntryPointParameters, EntryPointTypes, RustGpuEntryPointMetadata,
+    };
+
+    use bevy::utils::HashMap;
+
+    use super::{
+        export_dynamic_entry_point_to, file_reader, file_writer, write_generation,
+        EntryPointExport, EntryPointExportContainer, EntryPointExportPlugin, EntryPoints, Export,
+        ExportMergePolicy, ExportPruning, Permutation, PermutationUsage, EXPORT_HANDLES,
+    };
+
+    fn permutation(parameters: &[&str], usage: Option<PermutationUsage>) -> Permutation {
+        Permutation {
+            parameters: parameters.iter().map(ToString::to_string).collect(),
+            constants: vec![ShaderDefVal::UInt("MAX_LIGHTS".into(), 4)].into(),
+            usage,
+            ..Default::default()
+        }
+    }
+
+    fn usage(material: &str, time: u64) -> PermutationUsage {
+        let mut usage = PermutationUsage::default();
+        usage.record(material, time);
+        usage
+    }
+
+    #[test]
+    fn insert_permutation_keeps_order_and_rejects_duplicates() {
+        let mut entry_points = EntryPoints::default();
+
+        assert!(entry_points.insert_permutation("shader", permutation(&["b"], None)));
+        assert!(entry_points.insert_permutation("shader", permutation(&["a"], None)));
+        assert!(
+            !entry_points.insert_permutation("shader", permutation(&["b"], Some(usage("M", 1))))
+        );
+
+        assert_eq!(
+            entry_points.entry_points["shader"],
+            vec![permutation(&["a"], None), permutation(&["b"], None)]
+        );
+
+        assert!(entry_points.remove_permutation("shader", &permutation(&["a"], None)));
+        assert!(entry_points.remove_permutation("shader", &permutation(&["b"], None)));
+        assert!(!entry_points.contains_key("shader"));
+    }
+
+    #[test]
+    fn normalize_sorts_deduplicates_and_merges_usage() {
+        let mut entry_points = EntryPoints::default();
+        entry_points.insert(
+            "shader".into(),
+            vec![
+                permutation(&["b"], Some(usage("A", 20))),
+                permutation(&["a"], None),
+                permutation(&["b"], Some(usage("B", 10))),
+                permutation(&["a"], Some(usage("C", 30))),
+            ],
+        );
+
+        entry_points.normalize();
+
+        let permutations = &entry_points.entry_points["shader"];
+        assert_eq!(
+            permutations,
+            &vec![permutation(&["a"], None), permutation(&["b"], None)]
+        );
+
+        assert_eq!(permutations[0].usage, Some(usage("C", 30)));
+
+        let merged = permutations[1].usage.as_ref().unwrap();
+        assert_eq!(merged.count, 2);
+        assert_eq!((merged.first_seen, merged.last_seen), (10, 20));
+        assert_eq!(merged.materials.iter().collect::<Vec<_>>(), vec!["A", "B"]);
+    }
+
+    #[test]
+    fn file_writer_skips_identical_output() {
+        let path = std::env::temp_dir().join(format!(
+            "bevy-rust-gpu-file-writer-{:}.msgpack",
+            std::process::id()
+        ));
+
+        let mut entry_points = EntryPoints::default();
+        entry_points.insert_permutation("shader", permutation(&["a"], None));
+
+        let modified = || std::fs::metadata(&path).unwrap().modified().unwrap();
+        let backdate = || {
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1))
+                .unwrap()
+        };
+
+        file_writer(path.clone(), entry_points.clone()).unwrap();
+        backdate();
+
+        // Unnormalized but equivalent input should not be rewritten
+        let mut unnormalized = entry_points.clone();
+        unnormalized
+            .get_mut("shader")
+            .unwrap()
+            .push(permutation(&["a"], None));
+        file_writer(path.clone(), unnormalized).unwrap();
+        assert_eq!(modified(), SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+
+        entry_points.insert_permutation("shader", permutation(&["b"], None));
+        file_writer(path.clone(), entry_points).unwrap();
+        assert_ne!(modified(), SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn is_stale_respects_parameter_mismatch() {
+        let arities = HashMap::from_iter([("shader".to_string(), 2..=3)]);
+        let pruning = ExportPruning {
+            parameter_mismatch: true,
+            ..Default::default()
+        };
+
+        let stale = |shader: &str, parameters: &[&str]| {
+            pruning.is_stale(shader, &permutation(parameters, None), 0, &arities)
+        };
+
+        assert!(stale("shader", &["a"]));
+        assert!(!stale("shader", &["a", "b"]));
+        assert!(!stale("shader", &["a", "b", "c"]));
+        assert!(stale("shader", &["a", "b", "c", "d"]));
+
+        // Unknown entry points are never mismatched
+        assert!(!stale("unknown", &["a"]));
+
+        // Disabled criteria never prune
+        assert!(!ExportPruning::default().is_stale(
+            "shader",
+            &permutation(&["a"], None),
+            0,
+            &arities
+        ));
+    }
+
+    #[test]
+    fn is_stale_respects_sessions_unseen() {
+        let pruning = ExportPruning {
+            max_sessions_unseen: Some(2),
+            ..Default::default()
+        };
+
+        let stale = |sessions_unseen: u32| {
+            let mut usage = usage("M", 0);
+            usage.sessions_unseen = sessions_unseen;
+            pruning.is_stale(
+                "shader",
+                &permutation(&["a"], Some(usage)),
+                0,
+                &HashMap::default(),
+            )
+        };
+
+        assert!(!stale(0));
+        assert!(!stale(2));
+        assert!(stale(3));
+
+        // Permutations without statistics are kept
+        assert!(!pruning.is_stale("shader", &permutation(&["a"], None), 0, &HashMap::default()));
+    }
+
+    #[test]
+    fn is_stale_respects_max_age() {
+        let pruning = ExportPruning {
+            max_age: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+
+        let stale = |last_seen: u64, now: u64| {
+            pruning.is_stale(
+                "shader",
+                &permutation(&["a"], Some(usage("M", last_seen))),
+                now,
+                &HashMap::default(),
+            )
+        };
+
+        assert!(!stale(1000, 1000));
+        assert!(!stale(1000, 1060));
+        assert!(stale(1000, 1061));
+
+        // Timestamps from the future are not treated as stale
+        assert!(!stale(2000, 1000));
+
+        assert!(!pruning.is_stale(
+            "shader",
+            &permutation(&["a"], None),
+            5000,
+            &HashMap::default()
+        ));
+    }
+
+    #[test]
+    fn export_applies_merge_policy() {
+        let path = std::env::temp_dir().join(format!(
+            "bevy-rust-gpu-export-{:}.msgpack",
+            std::process::id()
+        ));
+
+        let export = |parameter: &str| Export {
+            shader: "shader".into(),
+            permutation: vec![parameter.to_string()],
+            constants: vec![ShaderDefVal::UInt("MAX_LIGHTS".into(), 4)],
+            ..Default::default()
+        };
+
+        let plugin = |merge_policy| EntryPointExportPlugin {
+            merge_policy,
+            ..Default::default()
+        };
+
+        let read = || file_reader(path.clone()).unwrap().unwrap();
+
+        plugin(ExportMergePolicy::Replace)
+            .export(&path, [export("a")])
+            .unwrap();
+        plugin(ExportMergePolicy::Accumulate)
+            .export(&path, [export("b")])
+            .unwrap();
+        assert_eq!(
+            read().entry_points["shader"],
+            vec![permutation(&["a"], None), permutation(&["b"], None)]
+        );
+
+        plugin(ExportMergePolicy::AccumulateWithExpiry(
+            Duration::from_secs(60),
+        ))
+        .export(&path, [export("b"), export("c")])
+        .unwrap();
+        assert_eq!(
+            read().entry_points["shader"],
+            vec![permutation(&["b"], None), permutation(&["c"], None)]
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Creates an export container for `path` as a running app would, then feeds it `exports`.
+    fn live_container(
+        path: &Path,
+        merge_policy: ExportMergePolicy,
+        exports: impl IntoIterator<Item = Export>,
+    ) -> EntryPointExportContainer {
+        let mut world = World::new();
+        world.insert_non_send_resource(EntryPointExport::default());
+
+        let mut create =
+            IntoSystem::into_system(EntryPointExport::create_export_containers_system(
+                file_reader,
+                merge_policy,
+                ExportPruning::default(),
+            ));
+        create.initialize(&mut world);
+        create.run((), &mut world);
+
+        let handle = EXPORT_HANDLES.read().unwrap()[path].clone();
+        for export in exports {
+            handle.send(export).unwrap();
+        }
+
+        let mut receive = IntoSystem::into_system(EntryPointExport::receive_entry_points_system);
+        receive.initialize(&mut world);
+        receive.run((), &mut world);
+
+        world
+            .remove_non_send_resource::<EntryPointExport>()
+            .unwrap()
+            .exports
+            .remove(path)
+            .unwrap()
+    }
+
+    #[test]
+    fn integer_constants_survive_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "bevy-rust-gpu-constants-{:}.json",
+            std::process::id()
+        ));
+
+        let entry_point = DynamicEntryPoint::new("export::tests::constants");
+        export_dynamic_entry_point_to(&entry_point, &path);
+
+        let export = || Export {
+            shader: entry_point.name.clone(),
+            constants: vec![
+                ShaderDefVal::Int("OFFSET".into(), 4),
+                ShaderDefVal::Int("BIAS".into(), -1),
+            ],
+            ..Default::default()
+        };
+
+        EntryPointExportPlugin::default()
+            .export(&path, [export()])
+            .unwrap();
+
+        for merge_policy in [
+            ExportMergePolicy::Accumulate,
+            ExportMergePolicy::AccumulateWithExpiry(Duration::from_secs(60)),
+        ] {
+            let container = live_container(&path, merge_policy, [export()]);
+            assert_eq!(container.entry_points[&entry_point.name].len(), 1);
+            assert!(container.changed.is_none());
+            if let Some((inherited, _)) = container.inherited {
+                assert!(inherited.is_empty());
+            }
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn live_containers_apply_merge_policy_on_create() {
+        let path =
+            std::env::temp_dir().join(format!("bevy-rust-gpu-merge-{:}.json", std::process::id()));
+
+        let entry_point = DynamicEntryPoint::new("export::tests::merge");
+        export_dynamic_entry_point_to(&entry_point, &path);
+
+        let export = |parameter: &str| Export {
+            shader: entry_point.name.clone(),
+            permutation: vec![parameter.to_string()],
+            constants: vec![ShaderDefVal::UInt("MAX_LIGHTS".into(), 4)],
+            ..Default::default()
+        };
+
+        for (merge_policy, expected, inherited) in [
+            (
+                ExportMergePolicy::Accumulate,
+                vec![permutation(&["a"], None), permutation(&["b"], None)],
+                None,
+            ),
+            (
+                ExportMergePolicy::Replace,
+                vec![permutation(&["b"], None)],
+                None,
+            ),
+            (
+                ExportMergePolicy::AccumulateWithExpiry(Duration::from_secs(60)),
+                vec![permutation(&["a"], None), permutation(&["b"], None)],
+                Some(vec![permutation(&["a"], None)]),
+            ),
+        ] {
+            EntryPointExportPlugin::default()
+                .export(&path, [export("a")])
+                .unwrap();
+
+            let container = live_container(&path, merge_policy, [export("b")]);
+            assert_eq!(
+                container.entry_points[&entry_point.name], expected,
+                "{merge_policy:?}"
+            );
+            assert_eq!(
+                container
+                    .inherited
+                    .map(|(inherited, _)| inherited.entry_points[&entry_point.name].clone()),
+                inherited,
+                "{merge_policy:?}"
+            );
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn delivers_permutations_queued_past_channel_capacity() {
+        let path =
+            std::env::temp_dir().join(format!("bevy-rust-gpu-queued-{:}.json", std::process::id()));
+
+        let entry_point = DynamicEntryPoint::new("export::tests::queued");
+        export_dynamic_entry_point_to(&entry_point, &path);
+
+        // Every permutation is sent before the receiving system first runs
+        let container = live_container(
+            &path,
+            ExportMergePolicy::Replace,
+            (0..100).map(|index| Export {
+                shader: entry_point.name.clone(),
+                permutation: vec![index.to_string()],
+                ..Default::default()
+            }),
+        );
+
+        assert_eq!(container.entry_points[&entry_point.name].len(), 100);
+        assert!(container.changed.is_some());
+    }
+
+    enum Generic {}
+
+    impl EntryPoint for Generic {
+        const NAME: &'static str = "generic";
+
+        fn parameters() -> EntryPointParameters {
+            &[(&[("VERTEX_UVS", "some")], "none")]
+        }
+
+        fn constants() -> EntryPointConstants {
+            &["MAX_LIGHTS", "SHADOWS"]
+        }
+
+        // Deliberately not in key order
+        fn types() -> EntryPointTypes {
+            vec![
+                ("Z".to_string(), "f32".to_string()),
+                ("A".to_string(), "u32".to_string()),
+            ]
+        }
+    }
+
+    #[test]
+    fn entry_point_name_matches_build_with() {
+        let layout = Mesh::new(PrimitiveTopology::TriangleList).get_mesh_vertex_buffer_layout();
+        let shader_defs = vec![
+            ShaderDefVal::Bool("VERTEX_UVS".into(), true),
+            ShaderDefVal::UInt("MAX_LIGHTS".into(), 4),
+            ShaderDefVal::Bool("SHADOWS".into(), true),
+            ShaderDefVal::Bool("UNRELATED".into(), true),
+        ];
+        let context = EntryPointContext {
+            shader_defs: &shader_defs,
+            layout: &layout,
+            mesh_key: EntryPointMeshKey::Mesh(MeshPipelineKey::NONE),
+            bind_group_data: None,
+        };
+
+        let mut entry_points = EntryPoints::default();
+        entry_points.insert_export(Export::entry_point::<Generic>("M", &context));
+
+        // Round-trip through serialization to check type order survives export
+        let entry_points: EntryPoints =
+            serde_json::from_str(&serde_json::to_string(&entry_points).unwrap()).unwrap();
+
+        let permutation = &entry_points.entry_points[Generic::NAME][0];
+        assert_eq!(
+            permutation.entry_point_name_for::<Generic>(),
+            Generic::build_with(&context)
+        );
+
+        // Without declaration order, types fall back to key order
+        assert_ne!(
+            permutation.entry_point_name(Generic::NAME),
+            Generic::build_with(&context)
+        );
+    }
+
+    #[test]
+    fn types_are_exported_as_a_map() {
+        let permutation = Permutation::from(Export {
+            types: vec![
+                ("Z".to_string(), "f32".to_string()),
+                ("A".to_string(), "u32".to_string()),
+            ],
+            ..Default::default()
+        });
+
+        let json = serde_json::to_string(&permutation).unwrap();
+        assert!(json.contains(r#""types":{"A":"u32","Z":"f32"}"#), "{json:}");
+
+        // Arrays of pairs are still read
+        let pairs: Permutation = serde_json::from_str(
+            r#"{"parameters":[],"constants":{},"types":[["Z","f32"],["A","u32"]]}"#,
+        )
+        .unwrap();
+        assert_eq!(pairs, permutation);
+
+        // Declaration order is taken from registered metadata
+        RustGpuEntryPointMetadata {
+            entry_points: vec![DynamicEntryPoint {
+                name: "export::tests::generic".to_string(),
+                types: vec![
+                    ("Z".to_string(), String::new()),
+                    ("A".to_string(), String::new()),
+                ],
+                ..Default::default()
+            }],
+        }
+        .register();
+
+        assert_eq!(
+            permutation.entry_point_name("export::tests::generic"),
+            permutation.entry_point_name_ordered("export::tests::generic", ["Z", "A"])
+        );
+        assert_ne!(
+            permutation.entry_point_name("export::tests::generic"),
+            permutation.entry_point_name("export::tests::unregistered")
+        );
+    }
+
+    #[test]
+    fn superseded_writes_are_skipped() {
+        let written = Mutex::new(0);
+
+        // A debounced write issued as generation 1 is still queued when the exit flush runs as generation 2
+        assert!(matches!(
+            write_generation(&written, 2, || Ok(())),
+            Some(Ok(()))
+        ));
+        assert!(write_generation(&written, 1, || panic!("stale write ran")).is_none());
+        assert_eq!(*written.lock().unwrap(), 2);
+    }
 }
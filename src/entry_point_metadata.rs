@@ -0,0 +1,325 @@
+//! Entry point metadata supplied by the shader crate.
+//!
+//! `rust-gpu-builder` output only lists mangled entry point names,
+//! so parameter and constant schemas are shipped in a sidecar `.rust-gpu-meta.json` / `.rust-gpu-meta.msgpack` file
+//! alongside the artifact, and registered when loaded via `AssetServer`.
+
+use std::{
+    ops::RangeInclusive,
+    sync::{Mutex, RwLock},
+};
+
+use bevy::{
+    prelude::{
+        default, info, AddAsset, AssetEvent, Assets, CoreSet, EventReader, IntoSystemConfig,
+        Plugin, Res,
+    },
+    reflect::TypeUuid,
+    render::render_resource::ShaderDefVal,
+    utils::{HashMap, HashSet},
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{
+    DynamicEntryPoint, DynamicEntryPointParameter, EntryPointConstants, EntryPointParameters,
+    EntryPointTypes,
+};
+
+/// Owned registry of loaded entry point metadata, keyed by entry point name.
+///
+/// Seeded with the metadata of any shader crates supported by this crate's features.
+static ENTRY_POINT_METADATA: Lazy<RwLock<HashMap<String, DynamicEntryPoint>>> = Lazy::new(|| {
+    #[allow(unused_mut)]
+    let mut registry = HashMap::default();
+
+    #[cfg(feature = "bevy-pbr-rust")]
+    for entry_point in crate::bevy_pbr_rust::bevy_pbr_rust_metadata().entry_points {
+        registry.insert(entry_point.name.clone(), entry_point);
+    }
+
+    RwLock::new(registry)
+});
+
+/// `'static` views of registered tables, as required by [`EntryPoint::parameters`](crate::prelude::EntryPoint::parameters)
+/// and [`EntryPoint::constants`](crate::prelude::EntryPoint::constants).
+///
+/// Interned by content, so reloading metadata only allocates tables that have not been seen before.
+#[derive(Default)]
+struct InternedTables {
+    strings: HashSet<&'static str>,
+    parameters: HashMap<Vec<DynamicEntryPointParameter>, EntryPointParameters>,
+    constants: HashMap<Vec<String>, EntryPointConstants>,
+}
+
+impl InternedTables {
+    fn string(&mut self, string: &str) -> &'static str {
+        if let Some(interned) = self.strings.get(string) {
+            return interned;
+        }
+
+        let interned: &'static str = Box::leak(string.to_string().into_boxed_str());
+        self.strings.insert(interned);
+        interned
+    }
+
+    fn parameters(&mut self, parameters: &[DynamicEntryPointParameter]) -> EntryPointParameters {
+        if let Some(interned) = self.parameters.get(parameters) {
+            return interned;
+        }
+
+        let interned = parameters
+            .iter()
+            .map(|parameter| {
+                let defined = parameter
+                    .defined
+                    .iter()
+                    .map(|(def, mapping)| (self.string(def), self.string(mapping)))
+                    .collect::<Vec<_>>();
+                (
+                    &*Box::leak(defined.into_boxed_slice()),
+                    self.string(&parameter.undefined),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let interned = &*Box::leak(interned.into_boxed_slice());
+        self.parameters.insert(parameters.to_vec(), interned);
+        interned
+    }
+
+    fn constants(&mut self, constants: &[String]) -> EntryPointConstants {
+        if let Some(interned) = self.constants.get(constants) {
+            return interned;
+        }
+
+        let interned = constants
+            .iter()
+            .map(|constant| self.string(constant))
+            .collect::<Vec<_>>();
+
+        let interned = &*Box::leak(interned.into_boxed_slice());
+        self.constants.insert(constants.to_vec(), interned);
+        interned
+    }
+}
+
+static INTERNED_TABLES: Lazy<Mutex<InternedTables>> = Lazy::new(default);
+
+/// Sidecar asset describing the parameters, constants and types of a shader crate's entry points.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, TypeUuid)]
+#[uuid = "4b17dd7c-e6c4-423b-ad9c-f8e005ef71dd"]
+#[serde(transparent)]
+pub struct RustGpuEntryPointMetadata {
+    pub entry_points: Vec<DynamicEntryPoint>,
+}
+
+impl RustGpuEntryPointMetadata {
+    /// Makes this metadata available to entry points implemented via [`metadata_entry_point`](crate::metadata_entry_point),
+    /// replacing any previously registered under the same names.
+    pub fn register(&self) {
+        let mut registry = ENTRY_POINT_METADATA.write().unwrap();
+        for entry_point in self.entry_points.iter() {
+            if registry.get(&entry_point.name) == Some(entry_point) {
+                continue;
+            }
+
+            info!("Registering metadata for entry point {:}", entry_point.name);
+            registry.insert(entry_point.name.clone(), entry_point.clone());
+        }
+    }
+}
+
+/// Returns the registered parameters of the entry point named `name`, or none if it has no metadata.
+pub fn metadata_parameters(name: &str) -> EntryPointParameters {
+    let registry = ENTRY_POINT_METADATA.read().unwrap();
+    let Some(entry_point) = registry.get(name) else {
+        return &[];
+    };
+
+    INTERNED_TABLES
+        .lock()
+        .unwrap()
+        .parameters(&entry_point.parameters)
+}
+
+/// Returns the registered constants of the entry point named `name`, or none if it has no metadata.
+pub fn metadata_constants(name: &str) -> EntryPointConstants {
+    let registry = ENTRY_POINT_METADATA.read().unwrap();
+    let Some(entry_point) = registry.get(name) else {
+        return &[];
+    };
+
+    INTERNED_TABLES
+        .lock()
+        .unwrap()
+        .constants(&entry_point.constants)
+}
+
+/// Returns the registered types of the entry point named `name`, or none if it has no metadata.
+pub fn metadata_types(name: &str) -> EntryPointTypes {
+    ENTRY_POINT_METADATA
+        .read()
+        .unwrap()
+        .get(name)
+        .map(|entry_point| entry_point.types.clone())
+        .unwrap_or_default()
+}
+
+/// Constructs a permutation set for the entry point named `name` from its registered metadata,
+/// as per [`EntryPoint::permutation`](crate::prelude::EntryPoint::permutation).
+///
+/// For use by hooks that extend the registered parameters.
+pub fn metadata_permutation(name: &str, shader_defs: &[ShaderDefVal]) -> Vec<String> {
+    ENTRY_POINT_METADATA
+        .read()
+        .unwrap()
+        .get(name)
+        .map(|entry_point| entry_point.permutation(shader_defs))
+        .unwrap_or_default()
+}
+
+/// Returns the registered parameter count of the entry point named `name`, widened by `extra`,
+/// as per [`EntryPoint::arity`](crate::prelude::EntryPoint::arity).
+///
+/// Entry points without metadata may produce permutations of any length.
+pub fn metadata_arity(name: &str, extra: RangeInclusive<usize>) -> RangeInclusive<usize> {
+    match ENTRY_POINT_METADATA.read().unwrap().get(name) {
+        Some(entry_point) => {
+            let parameters = entry_point.parameters.len();
+            parameters + extra.start()..=parameters + extra.end()
+        }
+        None => 0..=usize::MAX,
+    }
+}
+
+/// Implements the table items of [`EntryPoint`](crate::prelude::EntryPoint)
+/// by reading them from registered [`RustGpuEntryPointMetadata`] instead of declaring them in Rust.
+///
+/// Expands to `NAME`, `parameters`, `constants`, `types` and `arity` inside an `impl EntryPoint` block,
+/// leaving the remaining hooks free to be overridden.
+/// An optional second argument gives the range of parameters such hooks add on top of the registered ones.
+/// Until its metadata is registered, the entry point builds to its bare name.
+///
+/// ```ignore
+/// pub enum PbrFragment {}
+///
+/// impl EntryPoint for PbrFragment {
+///     metadata_entry_point!("pbr::entry_points::fragment");
+/// }
+/// ```
+#[macro_export]
+macro_rules! metadata_entry_point {
+    ($name: expr) => {
+        $crate::metadata_entry_point!($name, 0..=0);
+    };
+    ($name: expr, $extra: expr) => {
+        const NAME: &'static str = $name;
+
+        fn parameters() -> $crate::prelude::EntryPointParameters {
+            $crate::prelude::metadata_parameters(Self::NAME)
+        }
+
+        fn constants() -> $crate::prelude::EntryPointConstants {
+            $crate::prelude::metadata_constants(Self::NAME)
+        }
+
+        fn types() -> $crate::prelude::EntryPointTypes {
+            $crate::prelude::metadata_types(Self::NAME)
+        }
+
+        fn arity() -> ::std::ops::RangeInclusive<usize> {
+            $crate::prelude::metadata_arity(Self::NAME, $extra)
+        }
+    };
+}
+
+/// Adds support for [`RustGpuEntryPointMetadata`] assets.
+pub struct EntryPointMetadataPlugin;
+
+impl Plugin for EntryPointMetadataPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_asset::<RustGpuEntryPointMetadata>();
+
+        #[cfg(feature = "json")]
+        app.add_plugin(bevy_common_assets::json::JsonAssetPlugin::<
+            RustGpuEntryPointMetadata,
+        >::new(&["rust-gpu-meta.json"]));
+
+        #[cfg(feature = "msgpack")]
+        app.add_plugin(bevy_common_assets::msgpack::MsgPackAssetPlugin::<
+            RustGpuEntryPointMetadata,
+        >::new(&["rust-gpu-meta.msgpack"]));
+
+        app.add_system(entry_point_metadata_events.in_base_set(CoreSet::PreUpdate));
+    }
+}
+
+/// Registers [`RustGpuEntryPointMetadata`] assets as they are loaded or modified.
+pub fn entry_point_metadata_events(
+    mut events: EventReader<AssetEvent<RustGpuEntryPointMetadata>>,
+    assets: Res<Assets<RustGpuEntryPointMetadata>>,
+) {
+    for event in events.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
+            if let Some(metadata) = assets.get(handle) {
+                metadata.register();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::ShaderDefVal;
+
+    use crate::prelude::{DynamicEntryPoint, DynamicEntryPointParameter, EntryPoint};
+
+    use super::{metadata_parameters, RustGpuEntryPointMetadata};
+
+    enum Fragment {}
+
+    impl EntryPoint for Fragment {
+        crate::metadata_entry_point!("metadata::tests::fragment");
+    }
+
+    fn metadata(undefined: &str) -> RustGpuEntryPointMetadata {
+        RustGpuEntryPointMetadata {
+            entry_points: vec![DynamicEntryPoint {
+                name: Fragment::NAME.to_string(),
+                parameters: vec![DynamicEntryPointParameter {
+                    defined: vec![("VERTEX_UVS".to_string(), "some".to_string())],
+                    undefined: undefined.to_string(),
+                }],
+                constants: vec!["MAX_LIGHTS".to_string()],
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[test]
+    fn reads_registered_metadata() {
+        let shader_defs = vec![
+            ShaderDefVal::Bool("VERTEX_UVS".into(), true),
+            ShaderDefVal::UInt("MAX_LIGHTS".into(), 4),
+        ];
+
+        // Unregistered entry points build to their bare name, and may have any arity
+        assert_eq!(Fragment::build(&shader_defs), Fragment::NAME);
+        assert_eq!(Fragment::arity(), 0..=usize::MAX);
+
+        metadata("none").register();
+        assert_eq!(
+            Fragment::build(&shader_defs),
+            "metadata::tests::fragment__some__MAX_LIGHTS_4"
+        );
+        assert_eq!(Fragment::arity(), 1..=1);
+
+        // Reloading previously seen tables reuses their interned views
+        let parameters = metadata_parameters(Fragment::NAME);
+        metadata("absent").register();
+        assert_eq!(Fragment::parameters()[0].1, "absent");
+        metadata("none").register();
+        assert!(std::ptr::eq(parameters, Fragment::parameters()));
+    }
+}
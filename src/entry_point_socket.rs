@@ -0,0 +1,267 @@
+//! Streams entry point exports over a local TCP socket, bypassing the filesystem.
+//!
+//! # Message Format
+//!
+//! Messages are newline-delimited JSON objects tagged by a `kind` field:
+//!
+//! * `{"kind":"Snapshot","path":..,"entry_points":{..}}` carries the full set of entry points for an export.
+//!   A snapshot is sent for every known export whenever a connection is (re)established.
+//! * `{"kind":"Delta","path":..,"added":{..},"removed":{..}}` carries the permutations
+//!   added to and removed from an export since the previous message for that path.
+//!
+//! `entry_points`, `added` and `removed` use the same layout as a JSON export file.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{prelude::warn, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{EntryPoints, ExportError};
+
+/// Message sent from [`socket_writer`] to a listening builder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum SocketMessage {
+    /// Full set of entry points for an export.
+    Snapshot {
+        path: PathBuf,
+        entry_points: EntryPoints,
+    },
+    /// Changes to an export since the previous message for its path.
+    Delta {
+        path: PathBuf,
+        added: EntryPoints,
+        removed: EntryPoints,
+    },
+}
+
+impl SocketMessage {
+    /// Applies this message to a set of exports keyed by path.
+    pub fn apply(self, exports: &mut HashMap<PathBuf, EntryPoints>) {
+        match self {
+            SocketMessage::Snapshot { path, entry_points } => {
+                exports.insert(path, entry_points);
+            }
+            SocketMessage::Delta {
+                path,
+                added,
+                removed,
+            } => {
+                let export = exports.entry(path).or_default();
+
+                for (shader, permutations) in removed.entry_points {
+                    for permutation in permutations {
+                        export.remove_permutation(&shader, &permutation);
+                    }
+                }
+
+                for (shader, permutations) in added.entry_points {
+                    for permutation in permutations {
+                        export.insert_permutation(&shader, permutation);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Connection state shared between clones of a [`socket_writer`].
+#[derive(Debug, Default)]
+struct SocketWriterState {
+    stream: Option<TcpStream>,
+    /// Most recent entry points for each export, used to compute deltas and resend snapshots.
+    exports: HashMap<PathBuf, EntryPoints>,
+}
+
+impl SocketWriterState {
+    fn connect(&mut self, address: SocketAddr) -> Result<&mut TcpStream, ExportError> {
+        let mut stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+
+        for (path, entry_points) in self.exports.iter() {
+            send(
+                &mut stream,
+                &SocketMessage::Snapshot {
+                    path: path.clone(),
+                    entry_points: entry_points.clone(),
+                },
+            )?;
+        }
+
+        Ok(self.stream.insert(stream))
+    }
+}
+
+fn send(stream: &mut TcpStream, message: &SocketMessage) -> Result<(), ExportError> {
+    let mut line =
+        serde_json::to_vec(message).map_err(|e| ExportError::Serialize(e.to_string()))?;
+    line.push(b'\n');
+    stream.write_all(&line)?;
+    Ok(())
+}
+
+/// Export writer that streams entry points to a [`SocketMessage`] listener at `address`.
+///
+/// Connects lazily on the first write, and reconnects on the next write if the connection is lost,
+/// resending snapshots of every known export so the listener can recover its state.
+pub fn socket_writer(
+    address: SocketAddr,
+) -> impl Fn(PathBuf, EntryPoints) -> Result<(), ExportError> + Clone + Send + Sync + 'static {
+    let state = Arc::new(Mutex::new(SocketWriterState::default()));
+
+    move |path: PathBuf, mut entry_points: EntryPoints| {
+        entry_points.normalize();
+
+        let mut state = state.lock().unwrap();
+
+        let previous = state
+            .exports
+            .insert(path.clone(), entry_points.clone())
+            .unwrap_or_default();
+
+        if previous == entry_points && state.stream.is_some() {
+            return Ok(());
+        }
+
+        let message = SocketMessage::Delta {
+            path,
+            added: entry_points.difference(&previous),
+            removed: previous.difference(&entry_points),
+        };
+
+        // Try the existing connection first, then reconnect once.
+        // A fresh connection sends a snapshot that already includes this change.
+        if let Some(stream) = state.stream.as_mut() {
+            if send(stream, &message).is_ok() {
+                return Ok(());
+            }
+            state.stream = None;
+        }
+
+        state.connect(address)?;
+        Ok(())
+    }
+}
+
+/// Reference [`SocketMessage`] listener that can stand in for a builder in tests and tooling.
+pub struct SocketListener {
+    listener: TcpListener,
+}
+
+impl SocketListener {
+    pub fn bind<A: ToSocketAddrs>(address: A) -> std::io::Result<Self> {
+        Ok(SocketListener {
+            listener: TcpListener::bind(address)?,
+        })
+    }
+
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections in sequence, applying their messages to `exports`
+    /// and calling `f` after each one is applied.
+    ///
+    /// Malformed messages are logged and skipped.
+    /// Returns once `f` returns false, or on a listener error.
+    pub fn run<F>(
+        &self,
+        exports: &mut HashMap<PathBuf, EntryPoints>,
+        mut f: F,
+    ) -> std::io::Result<()>
+    where
+        F: FnMut(&HashMap<PathBuf, EntryPoints>) -> bool,
+    {
+        for stream in self.listener.incoming() {
+            for line in BufReader::new(stream?).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+
+                let message: SocketMessage = match serde_json::from_str(&line) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        warn!("Skipping malformed socket message: {e:}");
+                        continue;
+                    }
+                };
+
+                message.apply(exports);
+
+                if !f(exports) {
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, net::TcpStream, path::PathBuf};
+
+    use bevy::{render::render_resource::ShaderDefVal, utils::HashMap};
+
+    use crate::prelude::{EntryPoints, Export};
+
+    use super::{socket_writer, SocketListener};
+
+    fn entry_points(permutations: &[&str]) -> EntryPoints {
+        let mut entry_points = EntryPoints::default();
+        for permutation in permutations {
+            entry_points.insert_export(Export {
+                shader: "shader".into(),
+                permutation: vec![permutation.to_string()],
+                constants: vec![ShaderDefVal::Bool("DEF".into(), true)],
+                ..Default::default()
+            });
+        }
+        entry_points
+    }
+
+    #[test]
+    fn socket_writer_loopback() {
+        let listener = SocketListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let listener = std::thread::spawn(move || {
+            let mut exports = HashMap::default();
+            let mut messages = 0;
+            listener
+                .run(&mut exports, |_| {
+                    messages += 1;
+                    messages < 3
+                })
+                .unwrap();
+            exports
+        });
+
+        // Malformed messages should not stop the listener
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream
+            .write_all(b"not a message\n{\"kind\":\"Delta\"}\n")
+            .unwrap();
+        drop(stream);
+
+        let path = PathBuf::from("entry_points.json");
+        let writer = socket_writer(address);
+
+        // Initial connection sends a snapshot
+        writer(path.clone(), entry_points(&["a"])).unwrap();
+
+        // Subsequent writes send deltas
+        writer(path.clone(), entry_points(&["a", "b"])).unwrap();
+        writer(path.clone(), entry_points(&["b"])).unwrap();
+
+        let exports = listener.join().unwrap();
+        assert_eq!(exports.len(), 1);
+        assert_eq!(exports[&path], entry_points(&["b"]));
+    }
+}
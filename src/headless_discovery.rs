@@ -0,0 +1,391 @@
+//! Headless permutation discovery.
+//!
+//! Enumerates the entry point permutations a material would request for a set of
+//! mesh layouts, pipeline keys and material instances, without requiring a render device,
+//! so that exports can be regenerated in CI.
+
+use std::{any::Any, path::PathBuf};
+
+use bevy::{
+    pbr::{MeshPipelineKey, MAX_CASCADES_PER_LIGHT, MAX_DIRECTIONAL_LIGHTS},
+    prelude::{Material, Mesh},
+    render::{mesh::MeshVertexBufferLayout, render_resource::ShaderDefVal},
+};
+
+use crate::prelude::{
+    register_entry_point_arity, EntryPointContext, EntryPointExportPlugin, EntryPointMeshKey,
+    EntryPoints, Export, ExportError, RustGpuMaterial,
+};
+
+/// A [`RustGpuMaterial`] whose shader defs can be derived without a render device.
+///
+/// Implemented in terms of material instances rather than [`AsBindGroup::Data`](bevy::render::render_resource::AsBindGroup::Data),
+/// as the latter may be opaque outside of its defining crate.
+pub trait DiscoverMaterial: Material + RustGpuMaterial {
+    /// Appends the shader defs that this material's [`Material::specialize`] implementation
+    /// would add when specialized with the given mesh key and layout.
+    fn discovery_shader_defs(
+        &self,
+        _mesh_key: MeshPipelineKey,
+        _layout: &MeshVertexBufferLayout,
+        _vertex_shader_defs: &mut Vec<ShaderDefVal>,
+        _fragment_shader_defs: &mut Vec<ShaderDefVal>,
+    ) {
+    }
+
+    /// Returns the bind group data this material would produce,
+    /// for use by [`EntryPoint::permutation_with`](crate::prelude::EntryPoint::permutation_with) hooks.
+    fn discovery_bind_group_data(&self) -> Option<Self::Data> {
+        None
+    }
+}
+
+/// Returns the shader defs added by `MeshPipeline::specialize` for a given key and layout.
+///
+/// Mirrors the `bevy_pbr` implementation, which cannot be invoked without a render device,
+/// and is checked against it by this module's tests.
+/// Unlike upstream, `TONEMAP_METHOD_ACES_FITTED` is emitted without a trailing space.
+pub fn mesh_shader_defs(
+    key: MeshPipelineKey,
+    layout: &MeshVertexBufferLayout,
+) -> Vec<ShaderDefVal> {
+    let mut shader_defs: Vec<ShaderDefVal> = vec![];
+
+    if layout.contains(Mesh::ATTRIBUTE_POSITION) {
+        shader_defs.push("VERTEX_POSITIONS".into());
+    }
+
+    if layout.contains(Mesh::ATTRIBUTE_NORMAL) {
+        shader_defs.push("VERTEX_NORMALS".into());
+    }
+
+    shader_defs.push(ShaderDefVal::UInt(
+        "MAX_DIRECTIONAL_LIGHTS".to_string(),
+        MAX_DIRECTIONAL_LIGHTS as u32,
+    ));
+    shader_defs.push(ShaderDefVal::UInt(
+        "MAX_CASCADES_PER_LIGHT".to_string(),
+        MAX_CASCADES_PER_LIGHT as u32,
+    ));
+
+    if layout.contains(Mesh::ATTRIBUTE_UV_0) {
+        shader_defs.push("VERTEX_UVS".into());
+    }
+
+    if layout.contains(Mesh::ATTRIBUTE_TANGENT) {
+        shader_defs.push("VERTEX_TANGENTS".into());
+    }
+
+    if layout.contains(Mesh::ATTRIBUTE_COLOR) {
+        shader_defs.push("VERTEX_COLORS".into());
+    }
+
+    if key.msaa_samples() != 1 {
+        shader_defs.push("MULTISAMPLED".into());
+    }
+
+    if layout.contains(Mesh::ATTRIBUTE_JOINT_INDEX) && layout.contains(Mesh::ATTRIBUTE_JOINT_WEIGHT)
+    {
+        shader_defs.push("SKINNED".into());
+    }
+
+    let pass = key.intersection(MeshPipelineKey::BLEND_RESERVED_BITS);
+    if pass == MeshPipelineKey::BLEND_PREMULTIPLIED_ALPHA {
+        shader_defs.push("PREMULTIPLY_ALPHA".into());
+        shader_defs.push("BLEND_PREMULTIPLIED_ALPHA".into());
+    } else if pass == MeshPipelineKey::BLEND_MULTIPLY {
+        shader_defs.push("PREMULTIPLY_ALPHA".into());
+        shader_defs.push("BLEND_MULTIPLY".into());
+    }
+
+    if key.contains(MeshPipelineKey::TONEMAP_IN_SHADER) {
+        shader_defs.push("TONEMAP_IN_SHADER".into());
+
+        let method = key.intersection(MeshPipelineKey::TONEMAP_METHOD_RESERVED_BITS);
+        let method = [
+            (MeshPipelineKey::TONEMAP_METHOD_NONE, "TONEMAP_METHOD_NONE"),
+            (
+                MeshPipelineKey::TONEMAP_METHOD_REINHARD,
+                "TONEMAP_METHOD_REINHARD",
+            ),
+            (
+                MeshPipelineKey::TONEMAP_METHOD_REINHARD_LUMINANCE,
+                "TONEMAP_METHOD_REINHARD_LUMINANCE",
+            ),
+            (
+                MeshPipelineKey::TONEMAP_METHOD_ACES_FITTED,
+                "TONEMAP_METHOD_ACES_FITTED",
+            ),
+            (MeshPipelineKey::TONEMAP_METHOD_AGX, "TONEMAP_METHOD_AGX"),
+            (
+                MeshPipelineKey::TONEMAP_METHOD_SOMEWHAT_BORING_DISPLAY_TRANSFORM,
+                "TONEMAP_METHOD_SOMEWHAT_BORING_DISPLAY_TRANSFORM",
+            ),
+            (
+                MeshPipelineKey::TONEMAP_METHOD_BLENDER_FILMIC,
+                "TONEMAP_METHOD_BLENDER_FILMIC",
+            ),
+            (
+                MeshPipelineKey::TONEMAP_METHOD_TONY_MC_MAPFACE,
+                "TONEMAP_METHOD_TONY_MC_MAPFACE",
+            ),
+        ]
+        .into_iter()
+        .find_map(|(candidate, def)| (candidate == method).then_some(def));
+
+        if let Some(method) = method {
+            shader_defs.push(method.into());
+        }
+
+        // Debanding is tied to tonemapping in the shader, cannot run without it.
+        if key.contains(MeshPipelineKey::DEBAND_DITHER) {
+            shader_defs.push("DEBAND_DITHER".into());
+        }
+    }
+
+    if key.contains(MeshPipelineKey::ENVIRONMENT_MAP) {
+        shader_defs.push("ENVIRONMENT_MAP".into());
+    }
+
+    shader_defs
+}
+
+/// Describes the combinations of mesh layout, pipeline key and material to discover permutations for.
+///
+/// Every combination is specialized, so the number of discovered permutations
+/// is bounded by the product of the three lists' lengths.
+pub struct HeadlessDiscovery<M>
+where
+    M: DiscoverMaterial,
+{
+    pub layouts: Vec<MeshVertexBufferLayout>,
+    pub mesh_keys: Vec<MeshPipelineKey>,
+    pub materials: Vec<M>,
+}
+
+impl<M> Default for HeadlessDiscovery<M>
+where
+    M: DiscoverMaterial,
+{
+    fn default() -> Self {
+        HeadlessDiscovery {
+            layouts: vec![],
+            mesh_keys: vec![],
+            materials: vec![],
+        }
+    }
+}
+
+impl<M> HeadlessDiscovery<M>
+where
+    M: DiscoverMaterial,
+    M::Data: 'static,
+{
+    /// Returns the export messages that specializing each combination would produce.
+    pub fn exports(&self) -> Vec<Export> {
+        let mut exports = vec![];
+
+        for layout in self.layouts.iter() {
+            for mesh_key in self.mesh_keys.iter() {
+                for material in self.materials.iter() {
+                    let mut vertex_shader_defs = mesh_shader_defs(*mesh_key, layout);
+                    let mut fragment_shader_defs = vertex_shader_defs.clone();
+
+                    material.discovery_shader_defs(
+                        *mesh_key,
+                        layout,
+                        &mut vertex_shader_defs,
+                        &mut fragment_shader_defs,
+                    );
+
+                    let bind_group_data = material.discovery_bind_group_data();
+                    let bind_group_data = bind_group_data.as_ref().map(|data| data as &dyn Any);
+
+                    exports.push(Export::entry_point::<M::Vertex>(
+                        std::any::type_name::<M>(),
+                        &EntryPointContext {
+                            shader_defs: &vertex_shader_defs,
+                            layout,
+                            mesh_key: EntryPointMeshKey::Mesh(*mesh_key),
+                            bind_group_data,
+                        },
+                    ));
+                    exports.push(Export::entry_point::<M::Fragment>(
+                        std::any::type_name::<M>(),
+                        &EntryPointContext {
+                            shader_defs: &fragment_shader_defs,
+                            layout,
+                            mesh_key: EntryPointMeshKey::Mesh(*mesh_key),
+                            bind_group_data,
+                        },
+                    ));
+                }
+            }
+        }
+
+        exports
+    }
+
+    /// Returns the set of entry points that specializing each combination would produce.
+    pub fn entry_points(&self) -> EntryPoints {
+        let mut entry_points = EntryPoints::default();
+        for export in self.exports() {
+            entry_points.insert_export(export);
+        }
+        entry_points
+    }
+
+    /// Writes the discovered entry points to `path` via [`EntryPointExportPlugin::export`],
+    /// using its reader, writer, merge policy and pruning.
+    pub fn export_to<P, F>(
+        &self,
+        path: P,
+        plugin: &EntryPointExportPlugin<F>,
+    ) -> Result<(), ExportError>
+    where
+        P: Into<PathBuf>,
+        F: Fn(PathBuf, EntryPoints) -> Result<(), ExportError>,
+    {
+        register_entry_point_arity::<M::Vertex>();
+        register_entry_point_arity::<M::Fragment>();
+
+        plugin.export(path, self.exports())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        pbr::{MeshPipeline, MeshPipelineKey},
+        prelude::{default, App, AssetPlugin, FromWorld, ImagePlugin, Mesh, MinimalPlugins},
+        render::{
+            mesh::{
+                MeshVertexAttribute, MeshVertexBufferLayout, PrimitiveTopology,
+                VertexAttributeValues,
+            },
+            render_resource::{ShaderDefVal, SpecializedMeshPipeline},
+            RenderApp, RenderPlugin,
+        },
+        window::WindowPlugin,
+    };
+
+    use super::mesh_shader_defs;
+
+    fn layout(
+        attributes: impl IntoIterator<Item = (MeshVertexAttribute, VertexAttributeValues)>,
+    ) -> MeshVertexBufferLayout {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        for (attribute, values) in attributes {
+            mesh.insert_attribute(attribute, values);
+        }
+        mesh.get_mesh_vertex_buffer_layout()
+    }
+
+    /// Creates a real [`MeshPipeline`], panicking if no render device is available.
+    fn mesh_pipeline() -> MeshPipeline {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_plugin(WindowPlugin {
+                primary_window: None,
+                ..default()
+            })
+            .add_plugin(RenderPlugin::default())
+            .add_plugin(ImagePlugin::default());
+
+        MeshPipeline::from_world(&mut app.sub_app_mut(RenderApp).world)
+    }
+
+    #[test]
+    #[ignore = "requires a render device"]
+    fn mesh_shader_defs_match_mesh_pipeline() {
+        let pipeline = mesh_pipeline();
+
+        let position = || (Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new().into());
+        let normal = || (Mesh::ATTRIBUTE_NORMAL, Vec::<[f32; 3]>::new().into());
+        let uv = || (Mesh::ATTRIBUTE_UV_0, Vec::<[f32; 2]>::new().into());
+
+        let layouts = [
+            layout([position()]),
+            layout([position(), normal(), uv()]),
+            layout([
+                position(),
+                normal(),
+                uv(),
+                (Mesh::ATTRIBUTE_TANGENT, Vec::<[f32; 4]>::new().into()),
+                (Mesh::ATTRIBUTE_COLOR, Vec::<[f32; 4]>::new().into()),
+            ]),
+            layout([
+                position(),
+                normal(),
+                (
+                    Mesh::ATTRIBUTE_JOINT_INDEX,
+                    VertexAttributeValues::Uint16x4(vec![]),
+                ),
+                (Mesh::ATTRIBUTE_JOINT_WEIGHT, Vec::<[f32; 4]>::new().into()),
+            ]),
+        ];
+
+        let tonemapping = [
+            MeshPipelineKey::TONEMAP_METHOD_NONE,
+            MeshPipelineKey::TONEMAP_METHOD_REINHARD,
+            MeshPipelineKey::TONEMAP_METHOD_REINHARD_LUMINANCE,
+            MeshPipelineKey::TONEMAP_METHOD_ACES_FITTED,
+            MeshPipelineKey::TONEMAP_METHOD_AGX,
+            MeshPipelineKey::TONEMAP_METHOD_SOMEWHAT_BORING_DISPLAY_TRANSFORM,
+            MeshPipelineKey::TONEMAP_METHOD_BLENDER_FILMIC,
+            MeshPipelineKey::TONEMAP_METHOD_TONY_MC_MAPFACE,
+        ]
+        .into_iter()
+        .flat_map(|method| {
+            [
+                MeshPipelineKey::TONEMAP_IN_SHADER | method,
+                MeshPipelineKey::TONEMAP_IN_SHADER | method | MeshPipelineKey::DEBAND_DITHER,
+            ]
+        });
+
+        let keys = [
+            MeshPipelineKey::NONE,
+            MeshPipelineKey::BLEND_PREMULTIPLIED_ALPHA,
+            MeshPipelineKey::BLEND_MULTIPLY,
+            MeshPipelineKey::BLEND_ALPHA,
+            MeshPipelineKey::ENVIRONMENT_MAP,
+            MeshPipelineKey::DEBAND_DITHER,
+        ]
+        .into_iter()
+        .chain(tonemapping)
+        .flat_map(|key| {
+            [1, 4]
+                .into_iter()
+                .map(move |samples| key | MeshPipelineKey::from_msaa_samples(samples))
+        });
+
+        // Upstream emits `TONEMAP_METHOD_ACES_FITTED` with a trailing space
+        let trim = |defs: Vec<ShaderDefVal>| {
+            defs.into_iter()
+                .map(|def| match def {
+                    ShaderDefVal::Bool(key, value) => ShaderDefVal::Bool(key.trim().into(), value),
+                    def => def,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for key in keys {
+            for layout in layouts.iter() {
+                let descriptor = pipeline.specialize(key, layout).unwrap();
+                let expected = mesh_shader_defs(key, layout);
+
+                assert_eq!(
+                    trim(descriptor.vertex.shader_defs),
+                    expected,
+                    "vertex, {key:?}"
+                );
+                assert_eq!(
+                    trim(descriptor.fragment.unwrap().shader_defs),
+                    expected,
+                    "fragment, {key:?}"
+                );
+            }
+        }
+    }
+}
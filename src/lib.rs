@@ -8,23 +8,50 @@
 //! Can be used in conjunction with `rust-gpu-builder` and `permutate-macro`
 //! to drive a real-time shader recompilation pipeline.
 
+mod artifact_variant;
 mod builder_output;
+mod dynamic_entry_point;
 mod entry_point;
+mod entry_point_metadata;
+mod library;
 mod plugin;
 mod rust_gpu;
 mod rust_gpu_material;
+mod unmapped_shader_defs;
 
+pub use artifact_variant::*;
+pub use dynamic_entry_point::*;
 pub use entry_point::*;
+pub use entry_point_metadata::*;
+pub use library::*;
 pub use plugin::RustGpuPlugin;
 pub use rust_gpu::*;
 pub use rust_gpu_material::RustGpuMaterial;
+pub use unmapped_shader_defs::*;
 
 pub use rust_gpu_builder_shared::{RustGpuBuilderModules, RustGpuBuilderOutput};
 
+pub mod embedded_builder_output;
+
 #[cfg(feature = "hot-rebuild")]
 pub mod entry_point_export;
 
+#[cfg(feature = "hot-rebuild")]
+pub mod entry_point_diff;
+
+#[cfg(all(feature = "hot-rebuild", not(target_family = "wasm")))]
+pub mod entry_point_socket;
+
+#[cfg(feature = "hot-rebuild")]
+pub mod headless_discovery;
+
+#[cfg(feature = "hot-rebuild")]
+pub mod manifest;
+
 #[cfg(feature = "bevy-pbr-rust")]
 pub mod bevy_pbr_rust;
 
+#[cfg(feature = "wgsl")]
+pub mod wgsl;
+
 pub mod prelude;
@@ -0,0 +1,197 @@
+//! Layered artifact registry.
+//!
+//! Allows artifacts to be stacked over a base artifact,
+//! such that individual entry points can be overridden without rebuilding the base shader crate.
+
+use std::{collections::BTreeMap, sync::RwLock};
+
+use bevy::prelude::{default, warn, DetectChanges, Handle, Res, Resource, Shader};
+use once_cell::sync::Lazy;
+use rust_gpu_builder_shared::RustGpuBuilderOutput;
+
+use crate::prelude::RUST_GPU_ARTIFACTS;
+
+/// Static container for `RustGpuLibrary` to allow access from `Material::specialize`
+pub static RUST_GPU_LIBRARY: Lazy<RwLock<RustGpuLibrary>> = Lazy::new(default);
+
+/// Resource describing artifacts layered over base artifacts.
+///
+/// When a material references a base artifact, its layers are searched for entry points
+/// in priority order before falling back to the base artifact itself.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct RustGpuLibrary {
+    /// Layers stacked over each base artifact, in descending priority order.
+    pub layers: BTreeMap<Handle<RustGpuBuilderOutput>, Vec<Handle<RustGpuBuilderOutput>>>,
+}
+
+impl RustGpuLibrary {
+    /// Stacks `layer` over `base`, giving it priority over any existing layers.
+    pub fn push_layer(
+        &mut self,
+        base: Handle<RustGpuBuilderOutput>,
+        layer: Handle<RustGpuBuilderOutput>,
+    ) {
+        self.layers.entry(base).or_default().insert(0, layer);
+    }
+
+    /// Removes `layer` from the stack over `base`.
+    pub fn remove_layer(
+        &mut self,
+        base: &Handle<RustGpuBuilderOutput>,
+        layer: &Handle<RustGpuBuilderOutput>,
+    ) {
+        if let Some(layers) = self.layers.get_mut(base) {
+            layers.retain(|candidate| candidate != layer);
+        }
+    }
+
+    /// Returns the artifacts to search when resolving entry points for `base`,
+    /// consisting of its layers in priority order followed by `base` itself.
+    pub fn resolution_order<'a>(
+        &'a self,
+        base: &'a Handle<RustGpuBuilderOutput>,
+    ) -> impl Iterator<Item = &'a Handle<RustGpuBuilderOutput>> {
+        self.layers
+            .get(base)
+            .into_iter()
+            .flatten()
+            .chain(std::iter::once(base))
+    }
+
+    /// Returns true if `handle` is `base` or one of its layers.
+    pub fn contains(
+        &self,
+        base: &Handle<RustGpuBuilderOutput>,
+        handle: &Handle<RustGpuBuilderOutput>,
+    ) -> bool {
+        self.resolution_order(base)
+            .any(|candidate| candidate == handle)
+    }
+}
+
+/// Resolves an entry point against `base` and its library layers,
+/// returning the shader containing it and its name within that shader.
+pub fn resolve_entry_point(
+    base: &Handle<RustGpuBuilderOutput>,
+    entry_point: &str,
+) -> Option<(Handle<Shader>, String)> {
+    let library = RUST_GPU_LIBRARY.read().unwrap();
+    let artifacts = RUST_GPU_ARTIFACTS.read().unwrap();
+
+    let resolved = library.resolution_order(base).find_map(|handle| {
+        let Some(artifact) = artifacts.get(handle) else {
+            warn!("Missing artifact {:?}.", handle.id());
+            return None;
+        };
+
+        artifact.resolve(handle, entry_point)
+    });
+
+    resolved
+}
+
+/// Returns the artifact providing an entry point for `base`,
+/// searching its library layers in priority order without queueing any module loads.
+pub fn resolve_entry_point_artifact(
+    base: &Handle<RustGpuBuilderOutput>,
+    entry_point: &str,
+) -> Option<Handle<RustGpuBuilderOutput>> {
+    let library = RUST_GPU_LIBRARY.read().unwrap();
+    let artifacts = RUST_GPU_ARTIFACTS.read().unwrap();
+
+    let resolved = library
+        .resolution_order(base)
+        .find(|handle| {
+            artifacts.get(*handle).is_some_and(|artifact| {
+                artifact
+                    .entry_points
+                    .iter()
+                    .any(|candidate| candidate == entry_point)
+            })
+        })
+        .cloned();
+
+    resolved
+}
+
+/// System used to mirror the [`RustGpuLibrary`] resource into static storage on change.
+pub fn library_changed_system(library: Res<RustGpuLibrary>) {
+    if library.is_changed() {
+        *RUST_GPU_LIBRARY.write().unwrap() = library.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::HandleId,
+        prelude::{default, Handle, Shader},
+    };
+    use rust_gpu_builder_shared::RustGpuBuilderOutput;
+
+    use crate::prelude::{RustGpuArtifact, RustGpuModules, RUST_GPU_ARTIFACTS};
+
+    use super::{resolve_entry_point, resolve_entry_point_artifact, RUST_GPU_LIBRARY};
+
+    /// Registers a single-module artifact containing `entry_points`, returning its handle and shader.
+    fn artifact(entry_points: &[&str]) -> (Handle<RustGpuBuilderOutput>, Handle<Shader>) {
+        let handle = Handle::weak(HandleId::random::<RustGpuBuilderOutput>());
+        let shader = Handle::weak(HandleId::random::<Shader>());
+
+        RUST_GPU_ARTIFACTS.write().unwrap().insert(
+            handle.clone(),
+            RustGpuArtifact {
+                entry_points: entry_points.iter().map(ToString::to_string).collect(),
+                entry_point_names: default(),
+                modules: RustGpuModules::Single(shader.clone()),
+            },
+        );
+
+        (handle, shader)
+    }
+
+    #[test]
+    fn layers_resolve_in_priority_order() {
+        let (base, base_shader) = artifact(&["vertex", "fragment"]);
+        let (lower, lower_shader) = artifact(&["vertex", "fragment"]);
+        let (upper, upper_shader) = artifact(&["fragment"]);
+
+        {
+            let mut library = RUST_GPU_LIBRARY.write().unwrap();
+            library.push_layer(base.clone(), lower.clone());
+            library.push_layer(base.clone(), upper.clone());
+        }
+
+        // The most recently pushed layer wins, falling back through lower layers to the base
+        assert_eq!(
+            resolve_entry_point(&base, "fragment"),
+            Some((upper_shader, "fragment".to_string()))
+        );
+        assert_eq!(
+            resolve_entry_point(&base, "vertex"),
+            Some((lower_shader.clone(), "vertex".to_string()))
+        );
+        assert_eq!(resolve_entry_point(&base, "compute"), None);
+        assert_eq!(
+            resolve_entry_point_artifact(&base, "fragment"),
+            Some(upper.clone())
+        );
+
+        // Layers only apply to their own base
+        assert_eq!(
+            resolve_entry_point(&lower, "fragment").map(|(shader, _)| shader),
+            Some(lower_shader)
+        );
+
+        RUST_GPU_LIBRARY
+            .write()
+            .unwrap()
+            .remove_layer(&base, &lower);
+
+        assert_eq!(
+            resolve_entry_point(&base, "vertex"),
+            Some((base_shader, "vertex".to_string()))
+        );
+        assert_eq!(resolve_entry_point_artifact(&base, "vertex"), Some(base));
+    }
+}
@@ -0,0 +1,67 @@
+//! Checks that an artifact provides every entry point required by an exported manifest.
+//!
+//! Intended for use in release pipelines, where a missing permutation would otherwise
+//! only surface as a runtime warning and a silent fallback.
+
+use rust_gpu_builder_shared::RustGpuBuilderOutput;
+
+use crate::prelude::EntryPoints;
+
+/// Returns the mangled names of any entry points required by `manifest`
+/// that are not present in `output`, in manifest order.
+pub fn missing_entry_points(manifest: &EntryPoints, output: &RustGpuBuilderOutput) -> Vec<String> {
+    manifest
+        .entry_points
+        .iter()
+        .flat_map(|(shader, permutations)| {
+            permutations
+                .iter()
+                .map(move |permutation| permutation.entry_point_name(shader))
+        })
+        .filter(|name| !output.entry_points.contains(name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::ShaderDefVal;
+    use rust_gpu_builder_shared::{RustGpuBuilderModules, RustGpuBuilderOutput};
+
+    use crate::prelude::{EntryPoints, Export};
+
+    use super::missing_entry_points;
+
+    #[test]
+    fn reports_missing_entry_points_in_manifest_order() {
+        let mut manifest = EntryPoints::default();
+        for (shader, parameter) in [("b", "some"), ("a", "none"), ("a", "some")] {
+            manifest.insert_export(Export {
+                shader: shader.into(),
+                permutation: vec![parameter.into()],
+                constants: vec![ShaderDefVal::UInt("MAX_LIGHTS".into(), 4)],
+                ..Default::default()
+            });
+        }
+
+        let output = RustGpuBuilderOutput {
+            entry_points: vec!["a__some__MAX_LIGHTS_4".into(), "unrelated".into()],
+            modules: RustGpuBuilderModules::Single(vec![]),
+        };
+
+        assert_eq!(
+            missing_entry_points(&manifest, &output),
+            vec!["a__none__MAX_LIGHTS_4", "b__some__MAX_LIGHTS_4"]
+        );
+
+        let output = RustGpuBuilderOutput {
+            entry_points: vec![
+                "a__none__MAX_LIGHTS_4".into(),
+                "a__some__MAX_LIGHTS_4".into(),
+                "b__some__MAX_LIGHTS_4".into(),
+            ],
+            modules: RustGpuBuilderModules::Single(vec![]),
+        };
+
+        assert!(missing_entry_points(&manifest, &output).is_empty());
+    }
+}
@@ -1,10 +1,22 @@
 //! Main Rust-GPU plugin.
 
+#[cfg(feature = "hot-rebuild")]
 use std::path::PathBuf;
 
-use bevy::prelude::Plugin;
+#[cfg(not(feature = "hot-rebuild"))]
+use std::marker::PhantomData;
 
-use crate::prelude::{file_writer, BuilderOutputPlugin, EntryPoints};
+use bevy::prelude::{App, CoreSet, IntoSystemConfig, Plugin};
+
+use crate::prelude::{
+    render_device_features_system, unmapped_shader_def_events_system, BuilderOutputPlugin,
+    EntryPointMetadataPlugin, UnmappedShaderDef,
+};
+
+#[cfg(feature = "hot-rebuild")]
+use crate::prelude::{
+    file_writer, EntryPoints, ExportDebounce, ExportError, ExportMergePolicy, ExportPruning,
+};
 
 /// Main Rust-GPU plugin.
 ///
@@ -15,32 +27,93 @@ pub struct RustGpuPlugin<F> {
     #[cfg(feature = "hot-rebuild")]
     pub export_writer: F,
 
+    #[cfg(feature = "hot-rebuild")]
+    pub export_merge_policy: ExportMergePolicy,
+
+    #[cfg(feature = "hot-rebuild")]
+    pub export_debounce: ExportDebounce,
+
+    #[cfg(feature = "hot-rebuild")]
+    pub export_usage_statistics: bool,
+
+    #[cfg(feature = "hot-rebuild")]
+    pub export_pruning: ExportPruning,
+
     #[cfg(not(feature = "hot-rebuild"))]
     pub _phantom: PhantomData<F>,
 }
 
-impl Default for RustGpuPlugin<fn(PathBuf, EntryPoints)> {
+#[cfg(feature = "hot-rebuild")]
+impl Default for RustGpuPlugin<fn(PathBuf, EntryPoints) -> Result<(), ExportError>> {
     fn default() -> Self {
         Self {
             #[cfg(target_family = "wasm")]
-            export_writer: |_, _| (),
+            export_writer: |_, _| Ok(()),
 
             #[cfg(not(target_family = "wasm"))]
             export_writer: file_writer,
+
+            export_merge_policy: Default::default(),
+
+            export_debounce: Default::default(),
+
+            export_usage_statistics: false,
+
+            export_pruning: Default::default(),
         }
     }
 }
 
+#[cfg(not(feature = "hot-rebuild"))]
+impl Default for RustGpuPlugin<()> {
+    fn default() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F> RustGpuPlugin<F> {
+    /// Adds everything that does not depend on entry point export.
+    fn build_base(&self, app: &mut App) {
+        app.add_plugin(BuilderOutputPlugin);
+        app.add_plugin(EntryPointMetadataPlugin);
+
+        app.add_event::<UnmappedShaderDef>();
+        app.add_system(unmapped_shader_def_events_system.in_base_set(CoreSet::PreUpdate));
+
+        app.add_startup_system(render_device_features_system);
+    }
+}
+
+#[cfg(feature = "hot-rebuild")]
 impl<F> Plugin for RustGpuPlugin<F>
 where
-    F: Fn(PathBuf, EntryPoints) + Clone + Send + Sync + 'static,
+    F: Fn(PathBuf, EntryPoints) -> Result<(), ExportError> + Clone + Send + Sync + 'static,
 {
-    fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_plugin(BuilderOutputPlugin);
+    fn build(&self, app: &mut App) {
+        self.build_base(app);
 
-        #[cfg(feature = "hot-rebuild")]
         app.add_plugin(crate::prelude::EntryPointExportPlugin {
             writer: self.export_writer.clone(),
+            #[cfg(target_family = "wasm")]
+            reader: |_| Ok(None),
+            #[cfg(not(target_family = "wasm"))]
+            reader: crate::prelude::file_reader,
+            merge_policy: self.export_merge_policy,
+            debounce: self.export_debounce,
+            usage_statistics: self.export_usage_statistics,
+            pruning: self.export_pruning,
         });
     }
 }
+
+#[cfg(not(feature = "hot-rebuild"))]
+impl<F> Plugin for RustGpuPlugin<F>
+where
+    F: Send + Sync + 'static,
+{
+    fn build(&self, app: &mut App) {
+        self.build_base(app);
+    }
+}
@@ -1,4 +1,22 @@
-pub use crate::{builder_output::*, entry_point::*, plugin::*, rust_gpu::*, rust_gpu_material::*, *};
+pub use crate::{
+    artifact_variant::*, builder_output::*, embedded_builder_output::*, entry_point::*, library::*,
+    plugin::*, rust_gpu::*, rust_gpu_material::*, *,
+};
 
 #[cfg(feature = "hot-rebuild")]
 pub use crate::entry_point_export::*;
+
+#[cfg(feature = "hot-rebuild")]
+pub use crate::entry_point_diff::*;
+
+#[cfg(all(feature = "hot-rebuild", not(target_family = "wasm")))]
+pub use crate::entry_point_socket::*;
+
+#[cfg(feature = "hot-rebuild")]
+pub use crate::headless_discovery::*;
+
+#[cfg(feature = "hot-rebuild")]
+pub use crate::manifest::*;
+
+#[cfg(feature = "wgsl")]
+pub use crate::wgsl::*;
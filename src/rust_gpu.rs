@@ -6,8 +6,8 @@ use bevy::{
     asset::Asset,
     pbr::MaterialPipelineKey,
     prelude::{
-        default, info, warn, AssetEvent, Assets, CoreSet, EventReader, Handle, Image,
-        IntoSystemConfig, Material, MaterialPlugin, Plugin, ResMut,
+        default, info, warn, AssetEvent, Assets, CoreSet, DetectChanges, EventReader, Handle,
+        Image, IntoSystemConfig, Material, MaterialPlugin, Plugin, Res, ResMut,
     },
     reflect::TypeUuid,
     render::render_resource::{
@@ -19,7 +19,10 @@ use bevy::{
 use once_cell::sync::Lazy;
 use rust_gpu_builder_shared::RustGpuBuilderOutput;
 
-use crate::prelude::{EntryPoint, RustGpuMaterial};
+use crate::prelude::{
+    DynamicEntryPoint, EntryPoint, EntryPointContext, EntryPointMeshKey, RustGpuEntryPointMetadata,
+    RustGpuLibrary, RustGpuMaterial, RustGpuVariant,
+};
 
 static MATERIAL_SETTINGS: Lazy<RwLock<HashMap<TypeId, RustGpuSettings>>> = Lazy::new(default);
 
@@ -45,7 +48,7 @@ where
 impl<M> Plugin for RustGpuMaterialPlugin<M>
 where
     M: Material + RustGpuMaterial,
-    M::Data: Clone + Eq + std::hash::Hash,
+    M::Data: Clone + Eq + std::hash::Hash + 'static,
 {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_plugin(MaterialPlugin::<RustGpu<M>>::default());
@@ -75,7 +78,7 @@ where
 impl<M> Plugin for RustGpuMaterial2dPlugin<M>
 where
     M: Material2d + RustGpuMaterial,
-    M::Data: Clone + Eq + std::hash::Hash,
+    M::Data: Clone + Eq + std::hash::Hash + 'static,
 {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_plugin(Material2dPlugin::<RustGpu<M>>::default());
@@ -90,6 +93,10 @@ pub struct RustGpuSettings {
     pub fallback_base_vertex: bool,
     /// If true, use M::fragment as a fallback instead of ShaderRef::default
     pub fallback_base_fragment: bool,
+    /// If true, log a warning the first time a shader def is not mapped by the active entry point.
+    ///
+    /// [`UnmappedShaderDef`](crate::prelude::UnmappedShaderDef) events are sent regardless.
+    pub warn_unmapped_shader_defs: bool,
 }
 
 /// [`RustGpu`] pipeline key.
@@ -100,6 +107,12 @@ where
     pub base: M::Data,
     pub vertex_shader: Option<Handle<RustGpuBuilderOutput>>,
     pub fragment_shader: Option<Handle<RustGpuBuilderOutput>>,
+    pub vertex_variants: Vec<RustGpuVariant>,
+    pub fragment_variants: Vec<RustGpuVariant>,
+    pub vertex_entry_point: Option<String>,
+    pub fragment_entry_point: Option<String>,
+    pub vertex_dynamic_entry_point: Option<DynamicEntryPoint>,
+    pub fragment_dynamic_entry_point: Option<DynamicEntryPoint>,
     pub iteration: usize,
 }
 
@@ -113,6 +126,12 @@ where
             base: self.base.clone(),
             vertex_shader: self.vertex_shader.clone(),
             fragment_shader: self.fragment_shader.clone(),
+            vertex_variants: self.vertex_variants.clone(),
+            fragment_variants: self.fragment_variants.clone(),
+            vertex_entry_point: self.vertex_entry_point.clone(),
+            fragment_entry_point: self.fragment_entry_point.clone(),
+            vertex_dynamic_entry_point: self.vertex_dynamic_entry_point.clone(),
+            fragment_dynamic_entry_point: self.fragment_dynamic_entry_point.clone(),
             iteration: self.iteration.clone(),
         }
     }
@@ -127,6 +146,12 @@ where
         self.base.eq(&other.base)
             && self.vertex_shader.eq(&other.vertex_shader)
             && self.fragment_shader.eq(&other.fragment_shader)
+            && self.vertex_variants.eq(&other.vertex_variants)
+            && self.fragment_variants.eq(&other.fragment_variants)
+            && self.vertex_entry_point.eq(&other.vertex_entry_point)
+            && self.fragment_entry_point.eq(&other.fragment_entry_point)
+            && self.vertex_dynamic_entry_point.eq(&other.vertex_dynamic_entry_point)
+            && self.fragment_dynamic_entry_point.eq(&other.fragment_dynamic_entry_point)
             && self.iteration.eq(&other.iteration)
     }
 }
@@ -147,6 +172,12 @@ where
         self.base.hash(state);
         self.vertex_shader.hash(state);
         self.fragment_shader.hash(state);
+        self.vertex_variants.hash(state);
+        self.fragment_variants.hash(state);
+        self.vertex_entry_point.hash(state);
+        self.fragment_entry_point.hash(state);
+        self.vertex_dynamic_entry_point.hash(state);
+        self.fragment_dynamic_entry_point.hash(state);
         self.iteration.hash(state);
     }
 }
@@ -164,6 +195,38 @@ pub struct RustGpu<M> {
     /// If `Some`, overrides [`Material::fragment_shader`] during specialization.
     pub fragment_shader: Option<Handle<RustGpuBuilderOutput>>,
 
+    /// Feature-tagged alternatives to `vertex_shader`.
+    ///
+    /// The most specialized variant supported by the render device takes precedence over `vertex_shader`.
+    pub vertex_variants: Vec<RustGpuVariant>,
+
+    /// Feature-tagged alternatives to `fragment_shader`.
+    ///
+    /// The most specialized variant supported by the render device takes precedence over `fragment_shader`.
+    pub fragment_variants: Vec<RustGpuVariant>,
+
+    /// If `Some`, overrides the entry point built by [`RustGpuMaterial::Vertex`] for this instance.
+    ///
+    /// Validated against the selected shader in the same way, but not exported for hot-rebuilding.
+    /// Takes precedence over `vertex_dynamic_entry_point`.
+    pub vertex_entry_point: Option<String>,
+
+    /// If `Some`, overrides the entry point built by [`RustGpuMaterial::Fragment`] for this instance.
+    ///
+    /// Validated against the selected shader in the same way, but not exported for hot-rebuilding.
+    /// Takes precedence over `fragment_dynamic_entry_point`.
+    pub fragment_entry_point: Option<String>,
+
+    /// If `Some`, replaces [`RustGpuMaterial::Vertex`] for this instance.
+    ///
+    /// Built, validated and exported in the same way, allowing entry points to be chosen at runtime.
+    pub vertex_dynamic_entry_point: Option<DynamicEntryPoint>,
+
+    /// If `Some`, replaces [`RustGpuMaterial::Fragment`] for this instance.
+    ///
+    /// Built, validated and exported in the same way, allowing entry points to be chosen at runtime.
+    pub fragment_dynamic_entry_point: Option<DynamicEntryPoint>,
+
     /// Current reload iteration, used to drive hot-reloading.
     pub iteration: usize,
 }
@@ -176,6 +239,12 @@ where
         self.base.eq(&other.base)
             && self.vertex_shader.eq(&other.vertex_shader)
             && self.fragment_shader.eq(&other.fragment_shader)
+            && self.vertex_variants.eq(&other.vertex_variants)
+            && self.fragment_variants.eq(&other.fragment_variants)
+            && self.vertex_entry_point.eq(&other.vertex_entry_point)
+            && self.fragment_entry_point.eq(&other.fragment_entry_point)
+            && self.vertex_dynamic_entry_point.eq(&other.vertex_dynamic_entry_point)
+            && self.fragment_dynamic_entry_point.eq(&other.fragment_dynamic_entry_point)
             && self.iteration.eq(&other.iteration)
     }
 }
@@ -191,6 +260,12 @@ where
             (self.base.partial_cmp(&other.base)),
             (self.vertex_shader.partial_cmp(&other.vertex_shader)),
             (self.fragment_shader.partial_cmp(&other.fragment_shader)),
+            (self.vertex_variants.partial_cmp(&other.vertex_variants)),
+            (self.fragment_variants.partial_cmp(&other.fragment_variants)),
+            (self.vertex_entry_point.partial_cmp(&other.vertex_entry_point)),
+            (self.fragment_entry_point.partial_cmp(&other.fragment_entry_point)),
+            (self.vertex_dynamic_entry_point.partial_cmp(&other.vertex_dynamic_entry_point)),
+            (self.fragment_dynamic_entry_point.partial_cmp(&other.fragment_dynamic_entry_point)),
             (self.iteration.partial_cmp(&other.iteration)),
         ]
         .into_iter()
@@ -212,6 +287,12 @@ where
             (self.base.cmp(&other.base)),
             (self.vertex_shader.cmp(&other.vertex_shader)),
             (self.fragment_shader.cmp(&other.fragment_shader)),
+            (self.vertex_variants.cmp(&other.vertex_variants)),
+            (self.fragment_variants.cmp(&other.fragment_variants)),
+            (self.vertex_entry_point.cmp(&other.vertex_entry_point)),
+            (self.fragment_entry_point.cmp(&other.fragment_entry_point)),
+            (self.vertex_dynamic_entry_point.cmp(&other.vertex_dynamic_entry_point)),
+            (self.fragment_dynamic_entry_point.cmp(&other.fragment_dynamic_entry_point)),
             (self.iteration.cmp(&other.iteration)),
         ]
         .into_iter()
@@ -244,6 +325,12 @@ where
                     base: base.data,
                     vertex_shader: self.vertex_shader.clone(),
                     fragment_shader: self.fragment_shader.clone(),
+                    vertex_variants: self.vertex_variants.clone(),
+                    fragment_variants: self.fragment_variants.clone(),
+                    vertex_entry_point: self.vertex_entry_point.clone(),
+                    fragment_entry_point: self.fragment_entry_point.clone(),
+                    vertex_dynamic_entry_point: self.vertex_dynamic_entry_point.clone(),
+                    fragment_dynamic_entry_point: self.fragment_dynamic_entry_point.clone(),
                     iteration: self.iteration,
                 },
             })
@@ -259,132 +346,172 @@ where
 impl<M> RustGpu<M>
 where
     M: AsBindGroup + RustGpuMaterial + Send + Sync + 'static,
+    M::Data: 'static,
 {
     fn specialize_generic(
         descriptor: &mut bevy::render::render_resource::RenderPipelineDescriptor,
+        layout: &bevy::render::mesh::MeshVertexBufferLayout,
+        mesh_key: EntryPointMeshKey,
         key: RustGpuKey<M>,
     ) -> Result<(), SpecializedMeshPipelineError> {
         info!("Specializing RustGpu material");
+        let device_features = *crate::prelude::RENDER_DEVICE_FEATURES.read().unwrap();
+        let warn_unmapped_shader_defs = MATERIAL_SETTINGS
+            .read()
+            .unwrap()
+            .get(&TypeId::of::<Self>())
+            .map(|settings| settings.warn_unmapped_shader_defs)
+            .unwrap_or_default();
+
         let v = 'vertex: {
-            let Some(vertex_shader) = key.vertex_shader else {
+            let Some(vertex_shader) = crate::prelude::select_variant(&key.vertex_variants, device_features)
+                .map(|variant| variant.shader.clone())
+                .or(key.vertex_shader) else {
                 break 'vertex None;
             };
 
             info!("Vertex shader is present, aggregating defs");
 
-            let entry_point = M::Vertex::build(&descriptor.vertex.shader_defs);
+            let context = EntryPointContext {
+                shader_defs: &descriptor.vertex.shader_defs,
+                layout,
+                mesh_key,
+                bind_group_data: Some(&key.base),
+            };
+
+            let entry_point = match (&key.vertex_entry_point, &key.vertex_dynamic_entry_point) {
+                (Some(entry_point), _) => {
+                    info!("Using overridden vertex entrypoint {entry_point:}");
+                    entry_point.clone()
+                }
+                (None, Some(entry_point)) => entry_point.build_with(&context),
+                (None, None) => M::Vertex::build_with(&context),
+            };
             info!("Built vertex entrypoint {entry_point:}");
 
+            if key.vertex_entry_point.is_none() {
+                let (name, consumed) = match &key.vertex_dynamic_entry_point {
+                    Some(entry_point) => (entry_point.name.as_str(), entry_point.consumed_shader_defs()),
+                    None => (M::Vertex::NAME, M::Vertex::consumed_shader_defs()),
+                };
+
+                crate::prelude::record_unmapped_shader_defs(
+                    name,
+                    &consumed,
+                    std::any::type_name::<M>(),
+                    &descriptor.vertex.shader_defs,
+                    warn_unmapped_shader_defs,
+                );
+            }
+
             #[cfg(feature = "hot-rebuild")]
             'hot_rebuild: {
-                let exports = crate::prelude::MATERIAL_EXPORTS.read().unwrap();
-                let Some(export) = exports.get(&std::any::TypeId::of::<Self>()) else {
+                if key.vertex_entry_point.is_some() {
+                    break 'hot_rebuild;
+                }
+
+                let export = match &key.vertex_dynamic_entry_point {
+                    Some(entry_point) => crate::prelude::Export::dynamic_entry_point_with(entry_point, std::any::type_name::<M>(), &context),
+                    None => crate::prelude::Export::entry_point::<M::Vertex>(std::any::type_name::<M>(), &context),
+                };
+
+                let Some(path) = crate::prelude::export_route(&export.shader, &entry_point, std::any::TypeId::of::<Self>(), &vertex_shader) else {
                     break 'hot_rebuild;
                 };
 
                 let handles = crate::prelude::EXPORT_HANDLES.read().unwrap();
-                let Some(handle) = handles.get(export) else {
+                let Some(handle) = handles.get(&path) else {
                     break 'hot_rebuild;
                 };
 
                 info!("Entrypoint sender is valid");
-                handle
-                    .send(crate::prelude::Export {
-                        shader: M::Vertex::NAME,
-                        permutation: M::Vertex::permutation(&descriptor.vertex.shader_defs),
-                        constants: M::Vertex::filter_constants(&descriptor.vertex.shader_defs),
-                        types: M::Vertex::types()
-                            .into_iter()
-                            .map(|(key, value)| (key.to_string(), value.to_string()))
-                            .collect(),
-                    })
-                    .unwrap();
+                if let Err(e) = handle.send(export) {
+                    warn!("Failed to send vertex entry point for export: {e:}");
+                }
             };
 
             info!("Vertex meta is present");
-            let artifacts = crate::prelude::RUST_GPU_ARTIFACTS.read().unwrap();
-            let Some(artifact) = artifacts.get(&vertex_shader) else {
-                warn!("Missing vertex artifact.");
-                break 'vertex None;
-            };
-
             info!("Checking entry point {entry_point:}");
-            if !artifact.entry_points.contains(&entry_point) {
+            let Some((vertex_shader, entry_point)) = crate::prelude::resolve_entry_point(&vertex_shader, &entry_point) else {
                 warn!("Missing vertex entry point {entry_point:}.");
                 break 'vertex None;
-            }
-
-            let vertex_shader = match &artifact.modules {
-                crate::prelude::RustGpuModules::Single(single) => single.clone(),
-                crate::prelude::RustGpuModules::Multi(multi) => {
-                    let Some(shader) = multi.get(&entry_point) else {
-                        break 'vertex None;
-                    };
-
-                    shader.clone()
-                }
             };
 
             Some((vertex_shader, entry_point))
         };
 
         let f = 'fragment: {
-            let (Some(fragment_descriptor), Some(fragment_shader)) = (descriptor.fragment.as_mut(), key.fragment_shader) else { break 'fragment None };
+            let fragment_shader = crate::prelude::select_variant(&key.fragment_variants, device_features)
+                .map(|variant| variant.shader.clone())
+                .or(key.fragment_shader);
+
+            let (Some(fragment_descriptor), Some(fragment_shader)) = (descriptor.fragment.as_mut(), fragment_shader) else { break 'fragment None };
 
             info!("Fragment shader is present, aggregating defs");
 
-            let entry_point = M::Fragment::build(&fragment_descriptor.shader_defs);
+            let context = EntryPointContext {
+                shader_defs: &fragment_descriptor.shader_defs,
+                layout,
+                mesh_key,
+                bind_group_data: Some(&key.base),
+            };
+
+            let entry_point = match (&key.fragment_entry_point, &key.fragment_dynamic_entry_point) {
+                (Some(entry_point), _) => {
+                    info!("Using overridden fragment entrypoint {entry_point:}");
+                    entry_point.clone()
+                }
+                (None, Some(entry_point)) => entry_point.build_with(&context),
+                (None, None) => M::Fragment::build_with(&context),
+            };
             info!("Built fragment entrypoint {entry_point:}");
 
+            if key.fragment_entry_point.is_none() {
+                let (name, consumed) = match &key.fragment_dynamic_entry_point {
+                    Some(entry_point) => (entry_point.name.as_str(), entry_point.consumed_shader_defs()),
+                    None => (M::Fragment::NAME, M::Fragment::consumed_shader_defs()),
+                };
+
+                crate::prelude::record_unmapped_shader_defs(
+                    name,
+                    &consumed,
+                    std::any::type_name::<M>(),
+                    &fragment_descriptor.shader_defs,
+                    warn_unmapped_shader_defs,
+                );
+            }
+
             #[cfg(feature = "hot-rebuild")]
             'hot_rebuild: {
-                let exports = crate::prelude::MATERIAL_EXPORTS.read().unwrap();
-                let Some(export) = exports.get(&std::any::TypeId::of::<Self>()) else {
-                        break 'hot_rebuild;
-                    };
+                if key.fragment_entry_point.is_some() {
+                    break 'hot_rebuild;
+                }
+
+                let export = match &key.fragment_dynamic_entry_point {
+                    Some(entry_point) => crate::prelude::Export::dynamic_entry_point_with(entry_point, std::any::type_name::<M>(), &context),
+                    None => crate::prelude::Export::entry_point::<M::Fragment>(std::any::type_name::<M>(), &context),
+                };
+
+                let Some(path) = crate::prelude::export_route(&export.shader, &entry_point, std::any::TypeId::of::<Self>(), &fragment_shader) else {
+                    break 'hot_rebuild;
+                };
 
                 let handles = crate::prelude::EXPORT_HANDLES.read().unwrap();
-                let Some(handle) = handles.get(export) else {
-                        break 'hot_rebuild;
-                    };
+                let Some(handle) = handles.get(&path) else {
+                    break 'hot_rebuild;
+                };
 
                 info!("Entrypoint sender is valid");
-                handle
-                    .send(crate::prelude::Export {
-                        shader: M::Fragment::NAME,
-                        permutation: M::Fragment::permutation(&fragment_descriptor.shader_defs),
-                        constants: M::Fragment::filter_constants(&fragment_descriptor.shader_defs),
-                        types: M::Fragment::types()
-                            .into_iter()
-                            .map(|(key, value)| (key.to_string(), value.to_string()))
-                            .collect(),
-                    })
-                    .unwrap();
+                if let Err(e) = handle.send(export) {
+                    warn!("Failed to send fragment entry point for export: {e:}");
+                }
             }
 
             info!("Fragment meta is present");
-            let artifacts = crate::prelude::RUST_GPU_ARTIFACTS.read().unwrap();
-            let Some(artifact) = artifacts.get(&fragment_shader) else {
-                        warn!("Missing fragment artifact.");
-                        break 'fragment None;
-                    };
-
             info!("Checking entry point {entry_point:}");
-            if !artifact.entry_points.contains(&entry_point) {
+            let Some((fragment_shader, entry_point)) = crate::prelude::resolve_entry_point(&fragment_shader, &entry_point) else {
                 warn!("Missing fragment entry point {entry_point:}.");
                 break 'fragment None;
-            }
-
-            let fragment_shader = match &artifact.modules {
-                crate::prelude::RustGpuModules::Single(single) => single.clone(),
-                crate::prelude::RustGpuModules::Multi(multi) => {
-                    let Some(shader) = multi.get(&entry_point) else {
-                            warn!("Missing handle for entry point {entry_point:}.");
-                        break 'fragment None;
-                    };
-
-                    shader.clone()
-                }
             };
 
             Some((fragment_shader, entry_point))
@@ -432,7 +559,7 @@ where
 impl<M> Material for RustGpu<M>
 where
     M: Material + RustGpuMaterial,
-    M::Data: Clone,
+    M::Data: Clone + 'static,
 {
     fn vertex_shader() -> bevy::render::render_resource::ShaderRef {
         if let Some(true) = MATERIAL_SETTINGS
@@ -489,7 +616,12 @@ where
             },
         )?;
 
-        RustGpu::<M>::specialize_generic(descriptor, key.bind_group_data)?;
+        RustGpu::<M>::specialize_generic(
+            descriptor,
+            layout,
+            EntryPointMeshKey::Mesh(key.mesh_key),
+            key.bind_group_data,
+        )?;
 
         Ok(())
     }
@@ -498,7 +630,7 @@ where
 impl<M> Material2d for RustGpu<M>
 where
     M: Material2d + RustGpuMaterial,
-    M::Data: Clone,
+    M::Data: Clone + 'static,
 {
     fn vertex_shader() -> bevy::render::render_resource::ShaderRef {
         if let Some(true) = MATERIAL_SETTINGS
@@ -540,7 +672,12 @@ where
             },
         )?;
 
-        RustGpu::<M>::specialize_generic(descriptor, key.bind_group_data)?;
+        RustGpu::<M>::specialize_generic(
+            descriptor,
+            layout,
+            EntryPointMeshKey::Mesh2d(key.mesh_key),
+            key.bind_group_data,
+        )?;
 
         Ok(())
     }
@@ -556,9 +693,15 @@ where
     }
 
     #[cfg(feature = "hot-rebuild")]
-    pub fn export_to<P: Into<PathBuf>>(path: P) {
+    pub fn export_to<P: Into<PathBuf>>(path: P)
+    where
+        M: RustGpuMaterial,
+    {
         let mut handles = crate::prelude::MATERIAL_EXPORTS.write().unwrap();
         handles.insert(std::any::TypeId::of::<Self>(), path.into());
+
+        crate::prelude::register_entry_point_arity::<M::Vertex>();
+        crate::prelude::register_entry_point_arity::<M::Fragment>();
     }
 }
 
@@ -567,27 +710,57 @@ where
 /// Handles loading shader assets, maintaining static material data, and respecializing materials on reload.
 pub fn reload_materials<M>(
     mut builder_output_events: EventReader<AssetEvent<RustGpuBuilderOutput>>,
+    mut metadata_events: EventReader<AssetEvent<RustGpuEntryPointMetadata>>,
+    #[cfg(feature = "wgsl")] mut module_transpiled: EventReader<crate::wgsl::ModuleTranspiled>,
+    library: Res<RustGpuLibrary>,
     mut materials: ResMut<Assets<RustGpu<M>>>,
 ) where
     M: Asset + RustGpuMaterial,
 {
-    for event in builder_output_events.iter() {
-        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
-            // Mark any materials referencing this asset for respecialization
-            for (_, material) in materials.iter_mut() {
-                let mut reload = false;
-
-                if let Some(vertex_shader) = &material.vertex_shader {
-                    reload |= vertex_shader == handle;
-                }
+    // Changes to the library may alter entry point resolution, so respecialize everything
+    if library.is_changed() && !library.is_added() {
+        for (_, material) in materials.iter_mut() {
+            material.iteration += 1;
+        }
+    }
 
-                if let Some(fragment_shader) = &material.fragment_shader {
-                    reload |= fragment_shader == handle;
-                }
+    // Changes to entry point metadata may alter entry point names, so respecialize everything
+    if metadata_events
+        .iter()
+        .any(|event| matches!(event, AssetEvent::Created { .. } | AssetEvent::Modified { .. }))
+    {
+        for (_, material) in materials.iter_mut() {
+            material.iteration += 1;
+        }
+    }
 
-                if reload {
-                    material.iteration += 1;
-                }
+    let handles = builder_output_events.iter().filter_map(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle),
+        AssetEvent::Removed { .. } => None,
+    });
+
+    // Deferred modules have since been transpiled, so their entry points can now be resolved
+    #[cfg(feature = "wgsl")]
+    let handles = handles.chain(module_transpiled.iter().map(|event| &event.handle));
+
+    for handle in handles {
+        // Mark any materials referencing this asset or layering it for respecialization
+        for (_, material) in materials.iter_mut() {
+            let reload = material
+                .vertex_shader
+                .iter()
+                .chain(material.fragment_shader.iter())
+                .chain(
+                    material
+                        .vertex_variants
+                        .iter()
+                        .chain(material.fragment_variants.iter())
+                        .map(|variant| &variant.shader),
+                )
+                .any(|base| library.contains(base, handle));
+
+            if reload {
+                material.iteration += 1;
             }
         }
     }
@@ -0,0 +1,201 @@
+//! Detection of shader defs that the active entry point does not map.
+//!
+//! [`EntryPoint::permutation`](crate::prelude::EntryPoint::permutation) ignores unknown shader defs,
+//! so a def added upstream would otherwise silently have no effect on the selected entry point.
+
+use std::sync::Mutex;
+
+use bevy::{
+    prelude::{default, warn, EventWriter},
+    render::render_resource::ShaderDefVal,
+    utils::HashSet,
+};
+use once_cell::sync::Lazy;
+
+/// Entry point / shader def pairs that have already been reported.
+static REPORTED_SHADER_DEFS: Lazy<Mutex<HashSet<(String, String)>>> = Lazy::new(default);
+
+/// Static queue of unmapped shader defs detected in `Material::specialize`, awaiting event dispatch.
+static PENDING_SHADER_DEFS: Lazy<Mutex<Vec<UnmappedShaderDef>>> = Lazy::new(default);
+
+/// Event sent the first time an entry point is specialized with a shader def
+/// that none of its parameters or constants map.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnmappedShaderDef {
+    /// Base name of the entry point.
+    pub entry_point: String,
+    /// Type name of the material being specialized.
+    pub material: &'static str,
+    /// Name of the unmapped shader def.
+    pub shader_def: String,
+}
+
+/// Records any of `shader_defs` not among the `consumed` defs of `entry_point`, reporting each once.
+pub(crate) fn record_unmapped_shader_defs(
+    entry_point: &str,
+    consumed: &[&str],
+    material: &'static str,
+    shader_defs: &[ShaderDefVal],
+    warn: bool,
+) {
+    let mut reported = REPORTED_SHADER_DEFS.lock().unwrap();
+
+    for def in shader_defs {
+        let (ShaderDefVal::Bool(key, _) | ShaderDefVal::Int(key, _) | ShaderDefVal::UInt(key, _)) =
+            def;
+
+        // Upstream emits some defs with trailing whitespace
+        if consumed.contains(&key.trim())
+            || !reported.insert((entry_point.to_string(), key.clone()))
+        {
+            continue;
+        }
+
+        if warn {
+            warn!("Shader def {key:} is not mapped by entry point {entry_point:} of {material:}");
+        }
+
+        PENDING_SHADER_DEFS.lock().unwrap().push(UnmappedShaderDef {
+            entry_point: entry_point.to_string(),
+            material,
+            shader_def: key.clone(),
+        });
+    }
+}
+
+/// Dispatches [`UnmappedShaderDef`] events recorded during specialization.
+pub fn unmapped_shader_def_events_system(mut events: EventWriter<UnmappedShaderDef>) {
+    let pending = std::mem::take(&mut *PENDING_SHADER_DEFS.lock().unwrap());
+    events.send_batch(pending);
+}
+
+#[cfg(all(test, feature = "bevy-pbr-rust", feature = "hot-rebuild"))]
+mod tests {
+    use bevy::{
+        pbr::MeshPipelineKey,
+        prelude::{Mesh, StandardMaterial},
+        render::{
+            mesh::{MeshVertexBufferLayout, PrimitiveTopology},
+            render_resource::ShaderDefVal,
+        },
+    };
+
+    use crate::{
+        bevy_pbr_rust::{MeshVertex, PbrFragment},
+        prelude::{mesh_shader_defs, DiscoverMaterial, EntryPoint},
+    };
+
+    use super::{record_unmapped_shader_defs, UnmappedShaderDef, PENDING_SHADER_DEFS};
+
+    /// Records the defs of `material` specialized against every layout and key,
+    /// returning any that were reported.
+    fn reported(material: &'static str, upstream: &[ShaderDefVal]) -> Vec<UnmappedShaderDef> {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        let bare: MeshVertexBufferLayout = mesh.get_mesh_vertex_buffer_layout();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new());
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, Vec::<[f32; 3]>::new());
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, Vec::<[f32; 2]>::new());
+        mesh.insert_attribute(Mesh::ATTRIBUTE_TANGENT, Vec::<[f32; 4]>::new());
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, Vec::<[f32; 4]>::new());
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_JOINT_INDEX,
+            bevy::render::mesh::VertexAttributeValues::Uint16x4(vec![]),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, Vec::<[f32; 4]>::new());
+        let full = mesh.get_mesh_vertex_buffer_layout();
+
+        let tonemap_methods = [
+            MeshPipelineKey::TONEMAP_METHOD_NONE,
+            MeshPipelineKey::TONEMAP_METHOD_REINHARD,
+            MeshPipelineKey::TONEMAP_METHOD_REINHARD_LUMINANCE,
+            MeshPipelineKey::TONEMAP_METHOD_ACES_FITTED,
+            MeshPipelineKey::TONEMAP_METHOD_AGX,
+            MeshPipelineKey::TONEMAP_METHOD_SOMEWHAT_BORING_DISPLAY_TRANSFORM,
+            MeshPipelineKey::TONEMAP_METHOD_TONY_MC_MAPFACE,
+            MeshPipelineKey::TONEMAP_METHOD_BLENDER_FILMIC,
+        ];
+        let blend_modes = [
+            MeshPipelineKey::BLEND_OPAQUE,
+            MeshPipelineKey::BLEND_PREMULTIPLIED_ALPHA,
+            MeshPipelineKey::BLEND_MULTIPLY,
+            MeshPipelineKey::BLEND_ALPHA,
+        ];
+
+        let standard_material = StandardMaterial {
+            normal_map_texture: Some(Default::default()),
+            ..Default::default()
+        };
+
+        for layout in [&bare, &full] {
+            for (tonemap_method, blend_mode) in tonemap_methods
+                .into_iter()
+                .zip(blend_modes.into_iter().cycle())
+            {
+                let key = MeshPipelineKey::from_msaa_samples(4)
+                    | MeshPipelineKey::TONEMAP_IN_SHADER
+                    | MeshPipelineKey::DEBAND_DITHER
+                    | MeshPipelineKey::ENVIRONMENT_MAP
+                    | tonemap_method
+                    | blend_mode;
+
+                let mut vertex_shader_defs = mesh_shader_defs(key, layout);
+                let mut fragment_shader_defs = vertex_shader_defs.clone();
+                standard_material.discovery_shader_defs(
+                    key,
+                    layout,
+                    &mut vertex_shader_defs,
+                    &mut fragment_shader_defs,
+                );
+                fragment_shader_defs.push(ShaderDefVal::UInt(
+                    "AVAILABLE_STORAGE_BUFFER_BINDINGS".into(),
+                    3,
+                ));
+                fragment_shader_defs.extend(upstream.iter().cloned());
+
+                record_unmapped_shader_defs(
+                    MeshVertex::NAME,
+                    &MeshVertex::consumed_shader_defs(),
+                    material,
+                    &vertex_shader_defs,
+                    false,
+                );
+                record_unmapped_shader_defs(
+                    PbrFragment::NAME,
+                    &PbrFragment::consumed_shader_defs(),
+                    material,
+                    &fragment_shader_defs,
+                    false,
+                );
+            }
+        }
+
+        PENDING_SHADER_DEFS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|unmapped| unmapped.material == material)
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn standard_material_defs_are_consumed() {
+        assert_eq!(reported("unmapped_shader_defs::tests::Standard", &[]), []);
+    }
+
+    #[test]
+    fn reports_upstream_additions_once() {
+        let reported = reported(
+            "unmapped_shader_defs::tests::Upstream",
+            &["UPSTREAM_ADDITION".into()],
+        );
+
+        assert_eq!(
+            reported
+                .iter()
+                .map(|unmapped| (unmapped.entry_point.as_str(), unmapped.shader_def.as_str()))
+                .collect::<Vec<_>>(),
+            [(PbrFragment::NAME, "UPSTREAM_ADDITION")]
+        );
+    }
+}
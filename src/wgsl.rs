@@ -0,0 +1,201 @@
+//! SPIR-V to WGSL transpilation via `naga`.
+//!
+//! Allows `rust-gpu` shaders to be used on backends without SPIR-V passthrough support, such as WebGPU.
+
+use std::{collections::BTreeMap, sync::RwLock};
+
+use bevy::{
+    asset::HandleId,
+    prelude::{default, Handle},
+    utils::{HashMap, HashSet},
+};
+use once_cell::sync::Lazy;
+use rust_gpu_builder_shared::RustGpuBuilderOutput;
+
+/// Static cache of transpiled modules, keyed by their SPIR-V source.
+static TRANSPILE_CACHE: Lazy<RwLock<HashMap<Box<[u8]>, CachedModule>>> = Lazy::new(default);
+
+/// A transpilation result, alongside the artifacts whose modules produced it.
+struct CachedModule {
+    owners: HashSet<HandleId>,
+    transpiled: Result<TranspiledModule, TranspileError>,
+}
+
+/// A SPIR-V module converted to WGSL.
+#[derive(Debug, Clone)]
+pub struct TranspiledModule {
+    /// WGSL source.
+    pub source: String,
+    /// Maps SPIR-V entry point names to their sanitized WGSL equivalents.
+    pub entry_points: BTreeMap<String, String>,
+}
+
+/// Error produced when transpiling a SPIR-V module to WGSL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranspileError {
+    /// The SPIR-V module could not be parsed.
+    Parse(String),
+    /// The parsed module failed validation.
+    Validation(String),
+    /// The validated module could not be written as WGSL.
+    Write(String),
+    /// The WGSL name of an entry point could not be found in the written output.
+    Reparse(String),
+}
+
+impl std::fmt::Display for TranspileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranspileError::Parse(e) => write!(f, "SPIR-V parse error: {e:}"),
+            TranspileError::Validation(e) => write!(f, "Validation error: {e:}"),
+            TranspileError::Write(e) => write!(f, "WGSL write error: {e:}"),
+            TranspileError::Reparse(e) => write!(f, "WGSL reparse error: {e:}"),
+        }
+    }
+}
+
+impl std::error::Error for TranspileError {}
+
+/// Event sent when an entry point is unavailable due to its module failing to transpile.
+#[derive(Debug, Clone)]
+pub struct TranspileFailed {
+    pub handle: Handle<RustGpuBuilderOutput>,
+    pub entry_point: String,
+    pub error: TranspileError,
+}
+
+/// Event sent when a module deferred during specialization has been transpiled,
+/// such that materials using its entry points can be respecialized.
+#[derive(Debug, Clone)]
+pub struct ModuleTranspiled {
+    pub handle: Handle<RustGpuBuilderOutput>,
+    pub entry_point: String,
+}
+
+fn parse_spirv(module: &[u8]) -> Result<naga::Module, TranspileError> {
+    naga::front::spv::parse_u8_slice(
+        module,
+        &naga::front::spv::Options {
+            adjust_coordinate_space: false,
+            ..default()
+        },
+    )
+    .map_err(|e| TranspileError::Parse(e.to_string()))
+}
+
+/// Maps the SPIR-V names of a module's entry points to those the WGSL writer will assign them.
+///
+/// `naga` sanitizes entry point names that aren't valid WGSL identifiers,
+/// so the writer's namer is re-run to recover them.
+fn entry_point_names(module: &naga::Module) -> BTreeMap<String, String> {
+    let mut names = naga::FastHashMap::default();
+    naga::proc::Namer::default().reset(module, naga::keywords::wgsl::RESERVED, &["__"], &mut names);
+
+    module
+        .entry_points
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry_point)| {
+            let name = names.remove(&naga::proc::NameKey::EntryPoint(index as u16))?;
+            Some((entry_point.name.clone(), name))
+        })
+        .collect()
+}
+
+/// Converts a SPIR-V module to WGSL.
+///
+/// The output is parsed back to check that each entry point was written under its expected name.
+pub fn spirv_to_wgsl(module: &[u8]) -> Result<TranspiledModule, TranspileError> {
+    let module = parse_spirv(module)?;
+
+    let info = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|e| TranspileError::Validation(e.to_string()))?;
+
+    let source = naga::back::wgsl::write_string(
+        &module,
+        &info,
+        naga::back::wgsl::WriterFlags::EXPLICIT_TYPES,
+    )
+    .map_err(|e| TranspileError::Write(e.to_string()))?;
+
+    let written = naga::front::wgsl::parse_str(&source)
+        .map_err(|e| TranspileError::Reparse(e.emit_to_string(&source)))?;
+
+    let entry_points = entry_point_names(&module);
+    for entry_point in module.entry_points.iter() {
+        let found = entry_points.get(&entry_point.name).is_some_and(|name| {
+            written
+                .entry_points
+                .iter()
+                .any(|written| &written.name == name)
+        });
+
+        if !found {
+            return Err(TranspileError::Reparse(format!(
+                "Entry point {:} missing from WGSL output",
+                entry_point.name
+            )));
+        }
+    }
+
+    Ok(TranspiledModule {
+        source,
+        entry_points,
+    })
+}
+
+/// Converts a SPIR-V module belonging to artifact `owner` to WGSL,
+/// reusing the result of any previous conversion of the same module.
+pub fn spirv_to_wgsl_cached(
+    owner: &Handle<RustGpuBuilderOutput>,
+    module: &[u8],
+) -> Result<TranspiledModule, TranspileError> {
+    if let Some(cached) = TRANSPILE_CACHE.read().unwrap().get(module) {
+        if cached.owners.contains(&owner.id()) {
+            return cached.transpiled.clone();
+        }
+    }
+
+    let mut cache = TRANSPILE_CACHE.write().unwrap();
+    if let Some(cached) = cache.get_mut(module) {
+        cached.owners.insert(owner.id());
+        return cached.transpiled.clone();
+    }
+    drop(cache);
+
+    let transpiled = spirv_to_wgsl(module);
+
+    TRANSPILE_CACHE
+        .write()
+        .unwrap()
+        .entry(module.into())
+        .or_insert_with(|| CachedModule {
+            owners: default(),
+            transpiled: transpiled.clone(),
+        })
+        .owners
+        .insert(owner.id());
+
+    transpiled
+}
+
+/// Releases `owner`'s claim on any cached modules not contained in `retain`,
+/// evicting those no longer used by any artifact.
+///
+/// Called when an artifact is modified or removed, so stale modules don't accumulate across hot rebuilds.
+pub fn evict_transpiled<'a>(
+    owner: &Handle<RustGpuBuilderOutput>,
+    retain: impl IntoIterator<Item = &'a [u8]>,
+) {
+    let retain = retain.into_iter().collect::<HashSet<_>>();
+    TRANSPILE_CACHE.write().unwrap().retain(|module, cached| {
+        if !retain.contains(&**module) {
+            cached.owners.remove(&owner.id());
+        }
+        !cached.owners.is_empty()
+    });
+}